@@ -0,0 +1,136 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use r1cs::{
+    bn256::Fr,
+    halo2_proofs::{
+        circuit::Value,
+        plonk::{
+            Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed,
+            FloorPlanner, Instance, Selector,
+        },
+    },
+    R1CSCircuit, R1CS,
+};
+
+// x_{i+1} = x_i * x_i, with x_0 public: z = [1, x_0, x_1, ..., x_n]
+fn squarings(n: usize) -> (R1CS<Fr>, Vec<Fr>) {
+    let mut r1cs = R1CS::new(1, n);
+    let mut z = vec![Fr::one(), Fr::from(3)];
+    for i in 1..=n {
+        r1cs.add_constraint(vec![(i, Fr::one())], vec![(i, Fr::one())], vec![(i + 1, Fr::one())]);
+        z.push(z[i] * z[i]);
+    }
+    (r1cs, z)
+}
+
+/// Evaluates every assignment and discards it, so that only the floor planner
+/// and the chip are measured. `MockProver` would also allocate all `2^k` rows
+/// of every column, which at 2^20 constraints (k = 24) is several gigabytes
+/// per run.
+struct Discard;
+
+impl Assignment<Fr> for Discard {
+    fn enter_region<NR, N>(&mut self, _name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _annotation: A, _selector: &Selector, _row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn query_instance(&self, _column: Column<Instance>, _row: usize) -> Result<Value<Fr>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Advice>,
+        _row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _: Value<Assigned<Fr>> = to().map(Into::into);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Fixed>,
+        _row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _: Value<Assigned<Fr>> = to().map(Into::into);
+        Ok(())
+    }
+
+    fn copy(&mut self, _left: Column<Any>, _left_row: usize, _right: Column<Any>, _right_row: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _column: Column<Fixed>, _row: usize, _to: Value<Assigned<Fr>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _challenge: Challenge) -> Value<Fr> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _name: Option<String>) {}
+}
+
+fn synthesize(circuit: &R1CSCircuit<Fr>) {
+    let mut cs = ConstraintSystem::default();
+    let config = R1CSCircuit::<Fr>::configure(&mut cs);
+    let constants = cs.constants().clone();
+    <R1CSCircuit<Fr> as Circuit<Fr>>::FloorPlanner::synthesize(&mut Discard, circuit, config, constants).unwrap();
+}
+
+fn synthesis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("synthesis");
+    group.sample_size(10);
+
+    for log_n in [16, 18, 20] {
+        let (r1cs, z) = squarings(1 << log_n);
+
+        for (name, chunk_size) in [("per-constraint", 1), ("chunk-4096", 4096), ("single", usize::MAX)] {
+            let circuit = R1CSCircuit::<Fr>::new(r1cs.clone(), z.clone()).unwrap().with_chunk_size(chunk_size);
+
+            group.bench_with_input(BenchmarkId::new(name, log_n), &circuit, |b, circuit| {
+                b.iter(|| synthesize(circuit))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, synthesis);
+criterion_main!(benches);
@@ -0,0 +1,128 @@
+//! Import of arkworks constraint systems into an [`R1CS`].
+//!
+//! arkworks fields are distinct types from halo2's, so elements are moved
+//! through their canonical little-endian encoding after checking that both
+//! fields have the same prime. arkworks numbers variables as
+//! `[1, instance.., witness..]`, which is already the layout of `z`.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::ConstraintSystemRef;
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{error::Error, field, matrix::R1CS, r1cs::R1CSCircuit};
+
+/// Converts a synthesized constraint system into an R1CS over `F`, with its
+/// witness vector `z` if `cs` was synthesized in proving mode.
+///
+/// Symbolic linear combinations are inlined first, so `cs` must have been
+/// created with matrix construction enabled, which is the default.
+pub fn import<F: FieldExt, A: PrimeField>(
+    cs: ConstraintSystemRef<A>,
+) -> Result<(R1CS<F>, Option<Vec<F>>), Error> {
+    check_field::<F, A>()?;
+
+    cs.finalize();
+    let matrices = cs
+        .to_matrices()
+        .ok_or_else(|| Error::Unsupported("constraint system without matrices".to_string()))?;
+
+    // Both counts include the one wire as the first instance variable.
+    let num_inputs = matrices.num_instance_variables - 1;
+    let mut r1cs = R1CS::new(num_inputs, matrices.num_witness_variables);
+    for ((a, b), c) in matrices.a.iter().zip(matrices.b.iter()).zip(matrices.c.iter()) {
+        let lc = |row: &Vec<(A, usize)>| -> Result<_, Error> {
+            row.iter().map(|(coeff, wire)| Ok((*wire, convert(coeff)?))).collect()
+        };
+        r1cs.add_constraint(lc(a)?, lc(b)?, lc(c)?);
+    }
+
+    let z = match cs.borrow() {
+        Some(cs) if !cs.is_in_setup_mode() => {
+            let z = cs
+                .instance_assignment
+                .iter()
+                .chain(cs.witness_assignment.iter())
+                .map(convert)
+                .collect::<Result<Vec<F>, Error>>()?;
+            Some(z).filter(|z| z.len() == r1cs.num_variables())
+        }
+        _ => None,
+    };
+
+    Ok((r1cs, z))
+}
+
+/// Imports `cs` as an [`R1CSCircuit`], failing if it has no witness.
+pub fn import_circuit<F: FieldExt, A: PrimeField>(cs: ConstraintSystemRef<A>) -> Result<R1CSCircuit<F>, Error> {
+    match import(cs)? {
+        (r1cs, Some(z)) => R1CSCircuit::new(r1cs, z),
+        (_, None) => Err(Error::Format("constraint system has no witness".to_string())),
+    }
+}
+
+/// Rejects `A` unless its prime is the modulus of `F`.
+pub fn check_field<F: FieldExt, A: PrimeField>() -> Result<(), Error> {
+    field::check_modulus::<F>(&A::MODULUS.to_bytes_le())
+}
+
+fn convert<F: FieldExt, A: PrimeField>(value: &A) -> Result<F, Error> {
+    let bytes = value.into_bigint().to_bytes_le();
+    field::from_le_bytes(&bytes).ok_or_else(|| Error::OutOfRange {
+        value: field::le_bytes_to_hex(&bytes),
+        modulus: field::modulus_hex::<F>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import, import_circuit};
+    use crate::error::Error;
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisMode},
+    };
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::Fp},
+    };
+
+    type ArkFr = ark_bn254::Fr;
+
+    // x * x = y, with y public
+    fn square(cs: &ConstraintSystemRef<ArkFr>) {
+        let y = cs.new_input_variable(|| Ok(ArkFr::from(9u64))).unwrap();
+        let x = cs.new_witness_variable(|| Ok(ArkFr::from(3u64))).unwrap();
+        let x2 = lc!() + x;
+        let sym = cs.new_lc(x2).unwrap();
+        cs.enforce_constraint(lc!() + sym, lc!() + x, lc!() + y).unwrap();
+    }
+
+    #[test]
+    fn test_import() {
+        let cs = ConstraintSystem::<ArkFr>::new_ref();
+        square(&cs);
+        let (r1cs, z) = import::<Fr, _>(cs.clone()).unwrap();
+        assert_eq!((r1cs.num_inputs, r1cs.num_witnesses, r1cs.num_constraints()), (1, 1, 1));
+        assert_eq!(z, Some(vec![Fr::one(), Fr::from(9), Fr::from(3)]));
+
+        let circuit = import_circuit::<Fr, _>(cs).unwrap();
+        MockProver::run(circuit.min_k(), &circuit, vec![vec![Fr::from(9)]]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_import_setup_mode() {
+        let cs = ConstraintSystem::<ArkFr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        square(&cs);
+        let (r1cs, z) = import::<Fr, _>(cs).unwrap();
+        assert_eq!(r1cs.num_constraints(), 1);
+        assert_eq!(z, None);
+    }
+
+    #[test]
+    fn test_import_wrong_field() {
+        let cs = ConstraintSystem::<ArkFr>::new_ref();
+        square(&cs);
+        assert!(matches!(import::<Fp, _>(cs), Err(Error::FieldMismatch { .. })));
+    }
+}
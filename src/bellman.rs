@@ -0,0 +1,209 @@
+//! Recording bellman circuits into an [`R1CS`].
+//!
+//! [`Recorder`] implements bellman's `ConstraintSystem`, so an existing
+//! gadget's `Circuit::synthesize` can be run against it unchanged. Inputs and
+//! auxiliary variables become the public inputs and private witnesses of `z`,
+//! in allocation order, and each `enforce` becomes one constraint.
+
+use ::bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{matrix, matrix::R1CS, r1cs::R1CSCircuit};
+
+/// A bellman `ConstraintSystem` that records constraints and assignments.
+///
+/// Assignments are `None` when the circuit is synthesized without a witness,
+/// i.e. when an allocation returns `SynthesisError::AssignmentMissing`.
+pub struct Recorder<F: FieldExt> {
+    inputs: Vec<(String, Option<F>)>,
+    aux: Vec<(String, Option<F>)>,
+    constraints: Vec<(String, [LinearCombination<F>; 3])>,
+    namespace: Vec<String>,
+}
+
+impl<F: FieldExt> Default for Recorder<F> {
+    fn default() -> Self {
+        Recorder {
+            inputs: vec![("one".to_string(), Some(F::one()))],
+            aux: vec![],
+            constraints: vec![],
+            namespace: vec![],
+        }
+    }
+}
+
+impl<F: FieldExt> Recorder<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Synthesizes `circuit` into a fresh recorder.
+    pub fn record<C: Circuit<F>>(circuit: C) -> Result<Self, SynthesisError> {
+        let mut cs = Self::new();
+        circuit.synthesize(&mut cs)?;
+        Ok(cs)
+    }
+
+    /// The recorded constraints over `z = [1, inputs.., aux..]`.
+    pub fn r1cs(&self) -> R1CS<F> {
+        let mut r1cs = R1CS::new(self.inputs.len() - 1, self.aux.len());
+        for (_, [a, b, c]) in self.constraints.iter() {
+            r1cs.add_constraint(self.lc(a), self.lc(b), self.lc(c));
+        }
+        r1cs
+    }
+
+    /// The recorded assignment `z`, if every variable was assigned.
+    pub fn witness(&self) -> Option<Vec<F>> {
+        self.inputs.iter().chain(self.aux.iter()).map(|(_, value)| *value).collect()
+    }
+
+    /// The annotation of each wire of `z`, prefixed with its namespace.
+    pub fn names(&self) -> Vec<String> {
+        self.inputs.iter().chain(self.aux.iter()).map(|(name, _)| name.clone()).collect()
+    }
+
+    /// The annotation of each constraint, prefixed with its namespace.
+    pub fn constraint_names(&self) -> impl Iterator<Item = &str> {
+        self.constraints.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// An [`R1CSCircuit`] proving the recorded constraints, with wires named
+    /// after their annotations, or `None` if the witness is incomplete.
+    pub fn circuit(&self) -> Option<R1CSCircuit<F>> {
+        let z = self.witness()?;
+        let circuit = R1CSCircuit::new(self.r1cs(), z).expect("recorded constraints declare no prime and the witness has every wire");
+        Some(circuit.with_names(self.names()))
+    }
+
+    fn wire(&self, variable: &Variable) -> usize {
+        match variable.get_unchecked() {
+            Index::Input(i) => i,
+            Index::Aux(i) => self.inputs.len() + i,
+        }
+    }
+
+    fn lc(&self, lc: &LinearCombination<F>) -> matrix::LinearCombination<F> {
+        lc.as_ref().iter().map(|(variable, coeff)| (self.wire(variable), *coeff)).collect()
+    }
+
+    fn path(&self, name: String) -> String {
+        self.namespace.iter().chain(Some(&name)).cloned().collect::<Vec<_>>().join("/")
+    }
+}
+
+/// Runs an allocation, treating a missing assignment as unknown.
+fn assignment<F, A>(f: A) -> Result<Option<F>, SynthesisError>
+where
+    A: FnOnce() -> Result<F, SynthesisError>,
+{
+    match f() {
+        Ok(value) => Ok(Some(value)),
+        Err(SynthesisError::AssignmentMissing) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+impl<F: FieldExt> ConstraintSystem<F> for Recorder<F> {
+    type Root = Self;
+
+    fn alloc<V, A, AR>(&mut self, annotation: A, f: V) -> Result<Variable, SynthesisError>
+    where
+        V: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = assignment(f)?;
+        self.aux.push((self.path(annotation().into()), value));
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<V, A, AR>(&mut self, annotation: A, f: V) -> Result<Variable, SynthesisError>
+    where
+        V: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = assignment(f)?;
+        self.inputs.push((self.path(annotation().into()), value));
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        let lcs = [
+            a(LinearCombination::zero()),
+            b(LinearCombination::zero()),
+            c(LinearCombination::zero()),
+        ];
+        self.constraints.push((self.path(annotation().into()), lcs));
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recorder;
+    use ::bellman::{Circuit, ConstraintSystem, SynthesisError};
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr as Fp};
+
+    // x * x = y, with y public
+    struct Square {
+        x: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for Square {
+        fn synthesize<CS: ConstraintSystem<Fp>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            let mut cs = cs.namespace(|| "square");
+            let y = cs.alloc_input(|| "y", || self.x.map(|x| x * x).ok_or(SynthesisError::AssignmentMissing))?;
+            let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce(|| "x * x = y", |lc| lc + x, |lc| lc + x, |lc| lc + y);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let cs = Recorder::record(Square { x: Some(Fp::from(3)) }).unwrap();
+        let r1cs = cs.r1cs();
+        assert_eq!((r1cs.num_inputs, r1cs.num_witnesses, r1cs.num_constraints()), (1, 1, 1));
+        assert_eq!(cs.names(), vec!["one", "square/y", "square/x"]);
+        assert_eq!(cs.constraint_names().collect::<Vec<_>>(), vec!["square/x * x = y"]);
+
+        let z = cs.witness().unwrap();
+        assert_eq!(z, vec![Fp::one(), Fp::from(9), Fp::from(3)]);
+        assert_eq!(r1cs.is_satisfied(&z), Ok(()));
+
+        let circuit = cs.circuit().unwrap();
+        MockProver::run(circuit.min_k(), &circuit, vec![vec![Fp::from(9)]]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_record_without_witness() {
+        let cs = Recorder::record(Square { x: None }).unwrap();
+        assert_eq!(cs.r1cs().num_constraints(), 1);
+        assert!(cs.witness().is_none());
+        assert!(cs.circuit().is_none());
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use r1cs::{
+    bn256::Bn256,
+    circom, debug, format,
+    halo2_proofs::poly::{ipa::commitment::ParamsIPA, kzg::commitment::ParamsKZG},
+    optimize,
+    pasta::{EpAffine, EqAffine},
+    prover::{self, Backend},
+    Error, FieldExt, R1CSCircuit, Unsatisfied, R1CS,
+};
+
+/// Prove circom R1CS circuits with halo2.
+#[derive(Parser)]
+#[command(name = "r1cs-halo2", version)]
+struct Cli {
+    /// Commitment scheme; the R1CS must be over its scalar field.
+    #[arg(long, value_enum, global = true, default_value = "kzg-bn256")]
+    backend: BackendName,
+    /// Linear combination terms per row: 1, 2, 4 or 8. Wider layouts use
+    /// fewer rows and more columns; keys are only valid for their width.
+    #[arg(long, global = true, default_value_t = 1)]
+    width: usize,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BackendName {
+    /// KZG over BN254, for circom's default prime.
+    KzgBn256,
+    /// IPA on Vesta, for circuits over the Pallas base field (`--prime pallas`).
+    IpaVesta,
+    /// IPA on Pallas, for circuits over the Vesta base field (`--prime vesta`).
+    IpaPallas,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate parameters, unless `--params` exists, and the verifying key.
+    Setup {
+        /// `.r1cs` file, or `snarkjs r1cs export json` output.
+        #[arg(long)]
+        r1cs: PathBuf,
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        vk: PathBuf,
+        /// Circuit size; defaults to the smallest that fits the R1CS, and
+        /// must match `--params` if it exists.
+        #[arg(short, long)]
+        k: Option<u32>,
+    },
+    /// Prove a witness, writing the proof and its public inputs.
+    Prove {
+        #[arg(long)]
+        r1cs: PathBuf,
+        /// `.wtns` file, a JSON array of decimal strings, or with the `wasm`
+        /// feature a circom `.wasm` witness calculator run on `--input`.
+        #[arg(long)]
+        witness: PathBuf,
+        /// Input signals for a `.wasm` witness calculator, as JSON.
+        #[arg(long)]
+        input: Option<PathBuf>,
+        #[arg(long)]
+        params: PathBuf,
+        /// Verifying key from `setup`; generated from the R1CS if omitted.
+        #[arg(long)]
+        vk: Option<PathBuf>,
+        #[arg(long)]
+        proof: PathBuf,
+        /// Where to write the public inputs as JSON.
+        #[arg(long)]
+        public: PathBuf,
+        /// circom `.sym` file naming the wires in error reports.
+        #[arg(long)]
+        sym: Option<PathBuf>,
+    },
+    /// Verify a proof against its public inputs.
+    Verify {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        vk: PathBuf,
+        /// Proof file, which carries its public inputs.
+        #[arg(long)]
+        proof: PathBuf,
+        /// Public inputs the proof must have been made for, as JSON.
+        #[arg(long)]
+        public: Option<PathBuf>,
+    },
+    /// Print the size of an R1CS and the circuit it compiles to.
+    Info {
+        #[arg(long)]
+        r1cs: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.backend {
+        BackendName::KzgBn256 => with_width::<ParamsKZG<Bn256>>(cli.width, cli.command),
+        BackendName::IpaVesta => with_width::<ParamsIPA<EqAffine>>(cli.width, cli.command),
+        BackendName::IpaPallas => with_width::<ParamsIPA<EpAffine>>(cli.width, cli.command),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn with_width<P: Backend>(width: usize, command: Command) -> Result<(), Error> {
+    match width {
+        1 => run::<P, 1>(command),
+        2 => run::<P, 2>(command),
+        4 => run::<P, 4>(command),
+        8 => run::<P, 8>(command),
+        _ => Err(Error::Unsupported(format!("width {}", width))),
+    }
+}
+
+fn run<P: Backend, const WIDTH: usize>(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Setup { r1cs, params, vk, k } => {
+            let r1cs = load_r1cs::<P::Scalar>(&r1cs)?;
+            let circuit = R1CSCircuit::<P::Scalar, WIDTH>::without_witness(r1cs.clone())?;
+            let params = if params.exists() {
+                let existing = P::read(&mut BufReader::new(File::open(&params)?))?;
+                if let Some(k) = k.filter(|k| *k != existing.k()) {
+                    return Err(Error::Mismatch(format!("-k {} given, but --params has k = {}", k, existing.k())));
+                }
+                existing
+            } else {
+                let fresh = P::generate(k.unwrap_or_else(|| circuit.min_k()));
+                prover::write_params(&fresh, &mut BufWriter::new(File::create(&params)?))?;
+                fresh
+            };
+
+            let pk = prover::keygen(&params, &circuit)?;
+            format::write_vk(&params, pk.get_vk(), &circuit, &mut BufWriter::new(File::create(&vk)?))?;
+        }
+        Command::Prove { r1cs, witness, input, params, vk, proof, public, sym } => {
+            let r1cs = load_r1cs::<P::Scalar>(&r1cs)?;
+            let z = load_witness(&witness, input.as_deref())?;
+            match r1cs.is_satisfied(&z) {
+                Err(err @ Unsatisfied::Constraint(_)) => {
+                    let names = match sym {
+                        Some(sym) => circom::load_sym(&sym)?.names(&r1cs),
+                        None => vec![],
+                    };
+                    let circuit = R1CSCircuit::<P::Scalar, WIDTH>::new(r1cs.clone(), z)?.with_names(names);
+                    if let Err(diagnostics) = debug::verify(&circuit, circuit.min_k(), debug::default_names(&circuit)) {
+                        for diagnostic in diagnostics {
+                            eprint!("{}", diagnostic);
+                        }
+                    }
+                    return Err(err.into());
+                }
+                result => result?,
+            }
+            let public_inputs = r1cs.public_inputs(&z).expect("z has every variable").to_vec();
+            let circuit = R1CSCircuit::<P::Scalar, WIDTH>::new(r1cs.clone(), z)?;
+            let params = P::read(&mut BufReader::new(File::open(&params)?))?;
+            let pk = match vk {
+                Some(vk) => {
+                    let (header, vk) = format::read_vk::<P, WIDTH>(&params, &mut BufReader::new(File::open(&vk)?))?;
+                    header.check_r1cs(&r1cs)?;
+                    prover::keygen_pk_from_vk(&params, vk, &circuit)?
+                }
+                None => prover::keygen(&params, &circuit)?,
+            };
+
+            let bytes = prover::prove(&params, &pk, circuit)?;
+            let file = format::ProofFile::new(&r1cs, &public_inputs, bytes);
+            file.write(&mut BufWriter::new(File::create(&proof)?))?;
+            circom::write_witness_json(&public_inputs, BufWriter::new(File::create(&public)?))?;
+        }
+        Command::Verify { params, vk, proof, public } => {
+            let params = P::read(&mut BufReader::new(File::open(&params)?))?;
+            let (header, vk) = format::read_vk::<P, WIDTH>(&params, &mut BufReader::new(File::open(&vk)?))?;
+            let proof = format::ProofFile::<P::Scalar>::read(&mut BufReader::new(File::open(&proof)?))?;
+            if let Some(public) = public {
+                if circom::load_witness_json::<P::Scalar>(&public)? != proof.public_inputs {
+                    return Err(Error::Mismatch("public inputs differ from those of the proof".to_string()));
+                }
+            }
+
+            proof.verify(&params, &header, &vk)?;
+            println!("proof is valid");
+        }
+        Command::Info { r1cs } => {
+            let r1cs = load_r1cs::<P::Scalar>(&r1cs)?;
+            let budget = R1CSCircuit::<P::Scalar, WIDTH>::without_witness(r1cs.clone())?.row_budget();
+
+            println!("constraints:    {}", r1cs.num_constraints());
+            println!("variables:      {}", r1cs.num_variables());
+            println!("public inputs:  {}", r1cs.num_inputs);
+            println!("witnesses:      {}", r1cs.num_witnesses);
+            println!("rows:           {}", budget);
+            println!("minimum k:      {}", budget.min_k());
+
+            let (optimized, report) = optimize::eliminate_linear::<_, WIDTH>(&r1cs);
+            let optimized = R1CSCircuit::<P::Scalar, WIDTH>::without_witness(optimized)?.row_budget();
+            println!(
+                "linear elimination: {} -> {} constraints, {} constraint rows saved, minimum k {}",
+                report.constraints_before,
+                report.constraints_after,
+                report.rows_saved(),
+                optimized.min_k()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+fn load_r1cs<F: FieldExt>(path: &Path) -> Result<R1CS<F>, Error> {
+    if is_json(path) {
+        circom::load_r1cs_json(path)
+    } else {
+        circom::load_r1cs(path)?.into_r1cs()
+    }
+}
+
+fn load_witness<F: FieldExt>(path: &Path, input: Option<&Path>) -> Result<Vec<F>, Error> {
+    if path.extension().is_some_and(|ext| ext == "wasm") {
+        let input = input.ok_or_else(|| Error::Format("--input is required with a .wasm witness".to_string()))?;
+        return calculate_witness(path, input);
+    }
+    if is_json(path) {
+        circom::load_witness_json(path)
+    } else {
+        circom::load_wtns(path)
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn calculate_witness<F: FieldExt>(wasm: &Path, input: &Path) -> Result<Vec<F>, Error> {
+    circom::calculate_witness(wasm, input)
+}
+
+#[cfg(not(feature = "wasm"))]
+fn calculate_witness<F: FieldExt>(_wasm: &Path, _input: &Path) -> Result<Vec<F>, Error> {
+    Err(Error::Unsupported("witness calculators need the wasm feature".to_string()))
+}
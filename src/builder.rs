@@ -0,0 +1,337 @@
+//! Writing R1CS instances directly in Rust.
+//!
+//! [`R1CSBuilder`] allocates wires with their values and records constraints
+//! over linear combinations written with operators, e.g.
+//! `b.enforce(x + 3 * y - ONE, ONE, z)`, producing both the [`R1CS`] and its
+//! witness vector. Public inputs and witnesses may be allocated in any order;
+//! they are laid out as `z = [1, inputs.., witnesses..]` when built.
+
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{matrix::{LinearCombination, R1CS}, r1cs::R1CSCircuit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Var {
+    One,
+    Input(usize),
+    Witness(usize),
+}
+
+/// A wire allocated by an [`R1CSBuilder`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Wire<F> {
+    var: Var,
+    marker: PhantomData<F>,
+}
+
+impl<F> Clone for Wire<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for Wire<F> {}
+
+impl<F> Wire<F> {
+    fn new(var: Var) -> Self {
+        Wire { var, marker: PhantomData }
+    }
+}
+
+/// The constant one wire `z[0]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct One;
+
+/// The constant one wire `z[0]`, for use in linear combinations.
+pub const ONE: One = One;
+
+/// A linear combination of wires of an [`R1CSBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lc<F> {
+    terms: Vec<(Var, F)>,
+}
+
+impl<F: FieldExt> Lc<F> {
+    pub fn zero() -> Self {
+        Lc { terms: vec![] }
+    }
+
+    fn scale(mut self, k: F) -> Self {
+        for (_, coeff) in self.terms.iter_mut() {
+            *coeff *= k;
+        }
+        self
+    }
+}
+
+impl<F: FieldExt> From<Wire<F>> for Lc<F> {
+    fn from(wire: Wire<F>) -> Self {
+        Lc { terms: vec![(wire.var, F::one())] }
+    }
+}
+
+impl<F: FieldExt> From<One> for Lc<F> {
+    fn from(_: One) -> Self {
+        Lc { terms: vec![(Var::One, F::one())] }
+    }
+}
+
+/// A constant, as a multiple of the one wire.
+impl<F: FieldExt> From<F> for Lc<F> {
+    fn from(k: F) -> Self {
+        Lc { terms: vec![(Var::One, k)] }
+    }
+}
+
+impl<F: FieldExt, T: Into<Lc<F>>> Add<T> for Lc<F> {
+    type Output = Lc<F>;
+
+    fn add(mut self, rhs: T) -> Lc<F> {
+        self.terms.extend(rhs.into().terms);
+        self
+    }
+}
+
+impl<F: FieldExt, T: Into<Lc<F>>> Sub<T> for Lc<F> {
+    type Output = Lc<F>;
+
+    fn sub(self, rhs: T) -> Lc<F> {
+        self + -rhs.into()
+    }
+}
+
+impl<F: FieldExt> Neg for Lc<F> {
+    type Output = Lc<F>;
+
+    fn neg(self) -> Lc<F> {
+        self.scale(-F::one())
+    }
+}
+
+impl<F: FieldExt> Mul<F> for Lc<F> {
+    type Output = Lc<F>;
+
+    fn mul(self, k: F) -> Lc<F> {
+        self.scale(k)
+    }
+}
+
+impl<F: FieldExt, T: Into<Lc<F>>> Add<T> for Wire<F> {
+    type Output = Lc<F>;
+
+    fn add(self, rhs: T) -> Lc<F> {
+        Lc::from(self) + rhs
+    }
+}
+
+impl<F: FieldExt, T: Into<Lc<F>>> Sub<T> for Wire<F> {
+    type Output = Lc<F>;
+
+    fn sub(self, rhs: T) -> Lc<F> {
+        Lc::from(self) - rhs
+    }
+}
+
+impl<F: FieldExt> Neg for Wire<F> {
+    type Output = Lc<F>;
+
+    fn neg(self) -> Lc<F> {
+        -Lc::from(self)
+    }
+}
+
+impl<F: FieldExt> Mul<F> for Wire<F> {
+    type Output = Lc<F>;
+
+    fn mul(self, k: F) -> Lc<F> {
+        Lc::from(self) * k
+    }
+}
+
+/// `3 * y`; integer coefficients may be negative.
+impl<F: FieldExt> Mul<Wire<F>> for i64 {
+    type Output = Lc<F>;
+
+    fn mul(self, wire: Wire<F>) -> Lc<F> {
+        Lc::from(wire) * int(self)
+    }
+}
+
+impl<F: FieldExt> Mul<Lc<F>> for i64 {
+    type Output = Lc<F>;
+
+    fn mul(self, lc: Lc<F>) -> Lc<F> {
+        lc * int(self)
+    }
+}
+
+fn int<F: FieldExt>(k: i64) -> F {
+    let abs = F::from(k.unsigned_abs());
+    if k < 0 {
+        -abs
+    } else {
+        abs
+    }
+}
+
+/// Builds an [`R1CS`] and its witness vector `z` together.
+#[derive(Debug, Clone)]
+pub struct R1CSBuilder<F: FieldExt> {
+    inputs: Vec<F>,
+    witnesses: Vec<F>,
+    constraints: Vec<[Lc<F>; 3]>,
+}
+
+impl<F: FieldExt> Default for R1CSBuilder<F> {
+    fn default() -> Self {
+        R1CSBuilder {
+            inputs: vec![],
+            witnesses: vec![],
+            constraints: vec![],
+        }
+    }
+}
+
+impl<F: FieldExt> R1CSBuilder<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocates a public input with value `value`.
+    pub fn alloc_input(&mut self, value: F) -> Wire<F> {
+        self.inputs.push(value);
+        Wire::new(Var::Input(self.inputs.len() - 1))
+    }
+
+    /// Allocates a private witness with value `value`.
+    pub fn alloc_witness(&mut self, value: F) -> Wire<F> {
+        self.witnesses.push(value);
+        Wire::new(Var::Witness(self.witnesses.len() - 1))
+    }
+
+    /// Enforces `a * b = c`.
+    pub fn enforce(&mut self, a: impl Into<Lc<F>>, b: impl Into<Lc<F>>, c: impl Into<Lc<F>>) {
+        self.constraints.push([a.into(), b.into(), c.into()]);
+    }
+
+    /// Allocates the witness `a * b` and enforces it.
+    pub fn mul(&mut self, a: impl Into<Lc<F>>, b: impl Into<Lc<F>>) -> Wire<F> {
+        let (a, b) = (a.into(), b.into());
+        let c = self.alloc_witness(self.value(&a) * self.value(&b));
+        self.enforce(a, b, c);
+        c
+    }
+
+    /// The value of `lc` under the allocated values.
+    pub fn value(&self, lc: &Lc<F>) -> F {
+        lc.terms.iter().fold(F::zero(), |sum, (var, coeff)| {
+            let value = match var {
+                Var::One => F::one(),
+                Var::Input(i) => self.inputs[*i],
+                Var::Witness(i) => self.witnesses[*i],
+            };
+            sum + *coeff * value
+        })
+    }
+
+    /// The index in `z` of `wire`.
+    pub fn index(&self, wire: Wire<F>) -> usize {
+        self.column(wire.var)
+    }
+
+    fn column(&self, var: Var) -> usize {
+        match var {
+            Var::One => 0,
+            Var::Input(i) => 1 + i,
+            Var::Witness(i) => 1 + self.inputs.len() + i,
+        }
+    }
+
+    /// The constraints, with repeated wires merged and zero terms dropped.
+    pub fn r1cs(&self) -> R1CS<F> {
+        let lc = |lc: &Lc<F>| -> LinearCombination<F> {
+            let mut merged = BTreeMap::new();
+            for (var, coeff) in lc.terms.iter() {
+                *merged.entry(self.column(*var)).or_insert_with(F::zero) += *coeff;
+            }
+            merged.into_iter().filter(|(_, coeff)| *coeff != F::zero()).collect()
+        };
+
+        let mut r1cs = R1CS::new(self.inputs.len(), self.witnesses.len());
+        for [a, b, c] in self.constraints.iter() {
+            r1cs.add_constraint(lc(a), lc(b), lc(c));
+        }
+        r1cs
+    }
+
+    /// The witness vector `z = [1, inputs.., witnesses..]`.
+    pub fn witness(&self) -> Vec<F> {
+        Some(F::one())
+            .into_iter()
+            .chain(self.inputs.iter().copied())
+            .chain(self.witnesses.iter().copied())
+            .collect()
+    }
+
+    /// Returns the R1CS and its witness vector.
+    pub fn build(&self) -> (R1CS<F>, Vec<F>) {
+        (self.r1cs(), self.witness())
+    }
+
+    /// An [`R1CSCircuit`] proving the built R1CS, failing with the first
+    /// unsatisfied constraint.
+    pub fn circuit(&self) -> Result<R1CSCircuit<F>, crate::Error> {
+        let (r1cs, z) = self.build();
+        r1cs.is_satisfied(&z)?;
+        R1CSCircuit::new(r1cs, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lc, R1CSBuilder, ONE};
+    use crate::{Error, Unsatisfied};
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr as Fp};
+
+    #[test]
+    fn test_builder() {
+        // x^3 + x + 5 = out
+        let mut b = R1CSBuilder::<Fp>::new();
+        let x = b.alloc_witness(Fp::from(3));
+        let out = b.alloc_input(Fp::from(35));
+        let x2 = b.mul(x, x);
+        let x3 = b.mul(x2, x);
+        b.enforce(x3 + x + Fp::from(5), ONE, out);
+
+        let (r1cs, z) = b.build();
+        assert_eq!(b.index(out), 1);
+        assert_eq!(z, [1, 35, 3, 9, 27].map(Fp::from).to_vec());
+        assert_eq!(r1cs.is_satisfied(&z), Ok(()));
+
+        let circuit = b.circuit().unwrap();
+        MockProver::run(circuit.min_k(), &circuit, vec![vec![Fp::from(35)]]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut b = R1CSBuilder::<Fp>::new();
+        let x = b.alloc_witness(Fp::from(4));
+        let y = b.alloc_witness(Fp::from(2));
+        assert_eq!(b.value(&(x + 3 * y - ONE)), Fp::from(9));
+        assert_eq!(b.value(&(-2 * (x - y))), -Fp::from(4));
+
+        // x - x cancels out when built.
+        b.enforce(x - x, ONE, Lc::zero());
+        let r1cs = b.r1cs();
+        assert!(r1cs.a[0].is_empty());
+        assert!(r1cs.c[0].is_empty());
+
+        b.enforce(x, y, ONE);
+        assert!(matches!(b.circuit(), Err(Error::Unsatisfied(Unsatisfied::Constraint(1)))));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+use halo2_proofs::arithmetic::FieldExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, field, matrix::{LinearCombination, R1CS}};
+
+/// The output of `snarkjs r1cs export json`.
+///
+/// Coefficients and the prime are decimal strings, and each constraint is an
+/// `[A, B, C]` triple of `{ wire: coeff }` maps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct R1CSJson {
+    pub n8: u32,
+    pub prime: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
+    pub n_vars: usize,
+    pub n_outputs: usize,
+    pub n_pub_inputs: usize,
+    pub n_prv_inputs: usize,
+    pub n_labels: usize,
+    pub n_constraints: usize,
+    #[serde(default)]
+    pub use_custom_gates: bool,
+    pub constraints: Vec<[BTreeMap<usize, String>; 3]>,
+    #[serde(default)]
+    pub map: Vec<u64>,
+}
+
+impl R1CSJson {
+    /// Converts to an R1CS over `F`, rejecting another prime or out of range coefficients.
+    pub fn to_r1cs<F: FieldExt>(&self) -> Result<R1CS<F>, Error> {
+        let prime = check_prime::<F>(&self.prime)?;
+        if self.use_custom_gates {
+            return Err(Error::Unsupported("custom gates".to_string()));
+        }
+        if self.constraints.len() != self.n_constraints {
+            return Err(Error::Format(format!(
+                "nConstraints is {} but {} constraints are listed",
+                self.n_constraints,
+                self.constraints.len()
+            )));
+        }
+
+        let num_witnesses = self
+            .n_outputs
+            .checked_add(self.n_pub_inputs)
+            .and_then(|num_inputs| num_inputs.checked_add(1))
+            .and_then(|num_public| self.n_vars.checked_sub(num_public))
+            .ok_or_else(|| Error::Format("fewer variables than public signals".to_string()))?;
+        let num_inputs = self.n_outputs + self.n_pub_inputs;
+        let mut r1cs = R1CS::new(num_inputs, num_witnesses);
+        r1cs.prime = Some(prime);
+
+        for (row, [a, b, c]) in self.constraints.iter().enumerate() {
+            let lc = |terms: &BTreeMap<usize, String>| -> Result<LinearCombination<F>, Error> {
+                terms
+                    .iter()
+                    .map(|(&wire, coeff)| {
+                        if wire >= self.n_vars {
+                            return Err(Error::Format(format!(
+                                "constraint {}: wire {} out of range",
+                                row, wire
+                            )));
+                        }
+                        Ok((wire, parse_element(coeff)?))
+                    })
+                    .collect()
+            };
+            r1cs.add_constraint(lc(a)?, lc(b)?, lc(c)?);
+        }
+
+        Ok(r1cs)
+    }
+
+    /// Describes `r1cs` in the snarkjs format, with all public inputs counted
+    /// as inputs rather than outputs.
+    pub fn from_r1cs<F: FieldExt>(r1cs: &R1CS<F>) -> Self {
+        let lc = |terms: &[(usize, F)]| -> BTreeMap<usize, String> {
+            let mut map = BTreeMap::new();
+            for (wire, coeff) in terms {
+                let sum = map
+                    .get(wire)
+                    .and_then(|s: &String| field::from_decimal::<F>(s))
+                    .unwrap_or_else(F::zero)
+                    + coeff;
+                map.insert(*wire, field::to_decimal(&sum));
+            }
+            map
+        };
+
+        R1CSJson {
+            n8: field::repr_len::<F>() as u32,
+            prime: field::le_bytes_to_decimal(&field::modulus_le_bytes::<F>()),
+            curve: None,
+            n_vars: r1cs.num_variables(),
+            n_outputs: 0,
+            n_pub_inputs: r1cs.num_inputs,
+            n_prv_inputs: 0,
+            n_labels: r1cs.num_variables(),
+            n_constraints: r1cs.num_constraints(),
+            use_custom_gates: false,
+            constraints: r1cs.constraints().map(|(a, b, c)| [lc(a), lc(b), lc(c)]).collect(),
+            map: (0..r1cs.num_variables() as u64).collect(),
+        }
+    }
+}
+
+/// Parses `snarkjs r1cs export json` output over `F`.
+pub fn read_r1cs_json<F: FieldExt>(bytes: &[u8]) -> Result<R1CS<F>, Error> {
+    let json: R1CSJson = serde_json::from_slice(bytes).map_err(json_error)?;
+    json.to_r1cs()
+}
+
+/// Reads and parses the `r1cs.json` file at `path`.
+pub fn load_r1cs_json<F: FieldExt>(path: impl AsRef<Path>) -> Result<R1CS<F>, Error> {
+    read_r1cs_json(&fs::read(path)?)
+}
+
+/// Writes `r1cs` in the `snarkjs r1cs export json` format.
+pub fn write_r1cs_json<F: FieldExt>(r1cs: &R1CS<F>, writer: impl Write) -> Result<(), Error> {
+    serde_json::to_writer_pretty(writer, &R1CSJson::from_r1cs(r1cs)).map_err(json_error)
+}
+
+/// Parses a JSON witness, an array of decimal strings indexed by wire id.
+pub fn read_witness_json<F: FieldExt>(bytes: &[u8]) -> Result<Vec<F>, Error> {
+    let witness: Vec<String> = serde_json::from_slice(bytes).map_err(json_error)?;
+    witness.iter().map(|v| parse_element(v)).collect()
+}
+
+/// Reads and parses the `witness.json` file at `path`.
+pub fn load_witness_json<F: FieldExt>(path: impl AsRef<Path>) -> Result<Vec<F>, Error> {
+    read_witness_json(&fs::read(path)?)
+}
+
+/// Writes `z` as a JSON array of decimal strings.
+pub fn write_witness_json<F: FieldExt>(z: &[F], writer: impl Write) -> Result<(), Error> {
+    let witness: Vec<String> = z.iter().map(field::to_decimal).collect();
+    serde_json::to_writer_pretty(writer, &witness).map_err(json_error)
+}
+
+/// Parses the decimal `prime`, rejecting it unless it is the modulus of `F`.
+fn check_prime<F: FieldExt>(prime: &str) -> Result<Vec<u8>, Error> {
+    let bytes = field::decimal_to_le_bytes(prime)
+        .ok_or_else(|| Error::Format(format!("prime {:?} is not a decimal integer", prime)))?;
+    field::check_modulus::<F>(&bytes)?;
+    Ok(bytes)
+}
+
+pub(super) fn parse_element<F: FieldExt>(value: &str) -> Result<F, Error> {
+    if field::decimal_to_le_bytes(value).is_none() {
+        return Err(Error::Format(format!("{:?} is not a decimal integer", value)));
+    }
+    field::from_decimal(value).ok_or_else(|| Error::OutOfRange {
+        value: value.to_string(),
+        modulus: field::le_bytes_to_decimal(&field::modulus_le_bytes::<F>()),
+    })
+}
+
+pub(super) fn json_error(err: serde_json::Error) -> Error {
+    if err.is_io() {
+        Error::Io(err.into())
+    } else {
+        Error::Format(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
+
+    const SQUARE: &str = r#"{
+        "n8": 32,
+        "prime": "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        "curve": "bn128",
+        "nVars": 3,
+        "nOutputs": 1,
+        "nPubInputs": 0,
+        "nPrvInputs": 1,
+        "nLabels": 3,
+        "nConstraints": 1,
+        "useCustomGates": false,
+        "constraints": [[{"2": "1"}, {"2": "1"}, {"1": "1"}]],
+        "map": [0, 1, 2],
+        "customGates": [],
+        "customGatesUses": []
+    }"#;
+
+    #[test]
+    fn test_read_r1cs_json() {
+        let r1cs = read_r1cs_json::<Fr>(SQUARE.as_bytes()).unwrap();
+        let z = read_witness_json::<Fr>(br#"["1", "9", "3"]"#).unwrap();
+        assert_eq!(r1cs.num_inputs, 1);
+        assert_eq!(r1cs.is_satisfied(&z), Ok(()));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let r1cs = read_r1cs_json::<Fr>(SQUARE.as_bytes()).unwrap();
+        let mut out = vec![];
+        write_r1cs_json(&r1cs, &mut out).unwrap();
+        assert_eq!(read_r1cs_json::<Fr>(&out).unwrap(), r1cs);
+
+        let z = vec![Fr::one(), -Fr::one()];
+        let mut out = vec![];
+        write_witness_json(&z, &mut out).unwrap();
+        assert_eq!(read_witness_json::<Fr>(&out).unwrap(), z);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(read_r1cs_json::<Fp>(SQUARE.as_bytes()), Err(Error::FieldMismatch { .. })));
+
+        let out_of_range = SQUARE.replace(
+            r#"{"1": "1"}"#,
+            r#"{"1": "21888242871839275222246405745257275088548364400416034343698204186575808495617"}"#,
+        );
+        assert!(matches!(read_r1cs_json::<Fr>(out_of_range.as_bytes()), Err(Error::OutOfRange { .. })));
+        assert!(matches!(read_witness_json::<Fr>(br#"["0x1"]"#), Err(Error::Format(_))));
+
+        let overflow = SQUARE.replace(r#""nPubInputs": 0"#, &format!(r#""nPubInputs": {}"#, u64::MAX));
+        assert!(matches!(read_r1cs_json::<Fr>(overflow.as_bytes()), Err(Error::Format(_))));
+    }
+}
@@ -0,0 +1,110 @@
+//! Importers for the files produced by circom and snarkjs.
+//!
+//! The binary formats (`.r1cs`, `.wtns`) share the iden3 container layout: a
+//! four byte magic, a `u32` version and a list of `(type: u32, size: u64)`
+//! prefixed sections, all little-endian. The JSON formats are those written by
+//! `snarkjs r1cs export json` and `snarkjs wtns export json`. The `.sym`
+//! file names the wires of a `.r1cs` after the signals they carry. With the
+//! `wasm` feature, the `.wasm` witness calculator can be run in process.
+
+mod json;
+mod r1cs;
+mod sym;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod wtns;
+
+pub use self::json::{
+    load_r1cs_json, load_witness_json, read_r1cs_json, read_witness_json, write_r1cs_json,
+    write_witness_json, R1CSJson,
+};
+pub use self::r1cs::{load_r1cs, read_r1cs, CustomGate, CustomGateApplication, Header, R1CSFile};
+pub use self::sym::{load_sym, read_sym, Symbols};
+#[cfg(feature = "wasm")]
+pub use self::wasm::{calculate_witness, WitnessCalculator};
+pub use self::wtns::{load_wtns, read_wtns};
+
+use std::collections::HashMap;
+
+use crate::error::Error;
+
+/// Sections of an iden3 binary file, by section type.
+struct Sections<'a> {
+    version: u32,
+    sections: HashMap<u32, &'a [u8]>,
+}
+
+impl<'a> Sections<'a> {
+    fn parse(bytes: &'a [u8], magic: &[u8; 4]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        if reader.bytes(4)? != magic {
+            return Err(Error::Format(format!(
+                "expected magic {:?}",
+                String::from_utf8_lossy(magic)
+            )));
+        }
+        let version = reader.u32()?;
+        let n_sections = reader.u32()?;
+
+        let mut sections = HashMap::new();
+        for _ in 0..n_sections {
+            let section_type = reader.u32()?;
+            let size = reader.u64()? as usize;
+            if sections.insert(section_type, reader.bytes(size)?).is_some() {
+                return Err(Error::Format(format!("duplicate section {}", section_type)));
+            }
+        }
+
+        Ok(Sections { version, sections })
+    }
+
+    fn get(&self, section_type: u32) -> Option<Reader<'a>> {
+        self.sections.get(&section_type).map(|bytes| Reader::new(bytes))
+    }
+
+    fn require(&self, section_type: u32, name: &str) -> Result<Reader<'a>, Error> {
+        self.get(section_type)
+            .ok_or_else(|| Error::Format(format!("missing {} section", name)))
+    }
+}
+
+/// Little-endian cursor over a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| Error::Format("unexpected end of input".to_string()))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a NUL-terminated string.
+    fn string(&mut self) -> Result<String, Error> {
+        let rest = &self.bytes[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| Error::Format("unterminated string".to_string()))?;
+        let s = String::from_utf8(rest[..len].to_vec())
+            .map_err(|_| Error::Format("string is not utf-8".to_string()))?;
+        self.pos += len + 1;
+        Ok(s)
+    }
+}
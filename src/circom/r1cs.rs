@@ -0,0 +1,274 @@
+use std::{fs, path::Path};
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::{Reader, Sections};
+use crate::{error::Error, field, matrix::{LinearCombination, R1CS}};
+
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WIRE_TO_LABEL: u32 = 3;
+const CUSTOM_GATES_LIST: u32 = 4;
+const CUSTOM_GATES_APPLICATION: u32 = 5;
+
+/// Header section of a `.r1cs` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Size in bytes of a field element.
+    pub field_size: u32,
+    /// The field prime, little-endian.
+    pub prime: Vec<u8>,
+    pub n_wires: u32,
+    pub n_pub_out: u32,
+    pub n_pub_in: u32,
+    pub n_prv_in: u32,
+    pub n_labels: u64,
+    pub n_constraints: u32,
+}
+
+/// A PLONK custom gate template declared with `pragma custom_templates`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomGate<F> {
+    pub name: String,
+    pub parameters: Vec<F>,
+}
+
+/// An instantiation of a [`CustomGate`] over a list of wires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomGateApplication {
+    pub gate: u32,
+    pub signals: Vec<u32>,
+}
+
+/// A parsed circom `.r1cs` file.
+///
+/// Circom numbers wires as `[1, public outputs.., public inputs.., private
+/// inputs.., internal signals..]`, so wire ids index `z` directly and the
+/// outputs and public inputs together form the R1CS public inputs.
+#[derive(Debug, Clone)]
+pub struct R1CSFile<F: FieldExt> {
+    pub version: u32,
+    pub header: Header,
+    pub r1cs: R1CS<F>,
+    /// Label id of each wire, as used by the `.sym` file.
+    pub wire_to_label: Vec<u64>,
+    pub custom_gates: Vec<CustomGate<F>>,
+    pub custom_gate_applications: Vec<CustomGateApplication>,
+}
+
+/// Parses a `.r1cs` file over `F`, rejecting files produced for another prime.
+pub fn read_r1cs<F: FieldExt>(bytes: &[u8]) -> Result<R1CSFile<F>, Error> {
+    let sections = Sections::parse(bytes, b"r1cs")?;
+    if sections.version != 1 {
+        return Err(Error::Unsupported(format!("r1cs version {}", sections.version)));
+    }
+
+    let header = read_header(sections.require(HEADER, "header")?)?;
+    field::check_modulus::<F>(&header.prime)?;
+
+    let num_witnesses = u64::from(header.n_pub_out)
+        .checked_add(header.n_pub_in.into())
+        .and_then(|num_inputs| num_inputs.checked_add(1))
+        .and_then(|num_public| u64::from(header.n_wires).checked_sub(num_public))
+        .ok_or_else(|| Error::Format("fewer wires than public signals".to_string()))?;
+    // Both counts are now below `n_wires`, so they fit in a `u32`.
+    let num_inputs = (header.n_pub_out + header.n_pub_in) as usize;
+    let mut r1cs = R1CS::new(num_inputs, num_witnesses as usize);
+    r1cs.prime = Some(header.prime.clone());
+
+    let mut reader = sections.require(CONSTRAINTS, "constraints")?;
+    for _ in 0..header.n_constraints {
+        let a = read_lc(&mut reader, &header)?;
+        let b = read_lc(&mut reader, &header)?;
+        let c = read_lc(&mut reader, &header)?;
+        r1cs.add_constraint(a, b, c);
+    }
+
+    let wire_to_label = match sections.get(WIRE_TO_LABEL) {
+        Some(mut reader) => (0..header.n_wires)
+            .map(|_| reader.u64())
+            .collect::<Result<_, _>>()?,
+        None => vec![],
+    };
+
+    let custom_gates = match sections.get(CUSTOM_GATES_LIST) {
+        Some(mut reader) => {
+            let n = reader.u32()?;
+            (0..n)
+                .map(|_| {
+                    let name = reader.string()?;
+                    let n_parameters = reader.u32()?;
+                    let parameters = (0..n_parameters)
+                        .map(|_| read_element(&mut reader, &header))
+                        .collect::<Result<_, _>>()?;
+                    Ok(CustomGate { name, parameters })
+                })
+                .collect::<Result<_, Error>>()?
+        }
+        None => vec![],
+    };
+
+    let custom_gate_applications = match sections.get(CUSTOM_GATES_APPLICATION) {
+        Some(mut reader) => {
+            let n = reader.u32()?;
+            (0..n)
+                .map(|_| {
+                    let gate = reader.u32()?;
+                    let n_signals = reader.u32()?;
+                    let signals = (0..n_signals).map(|_| reader.u32()).collect::<Result<_, _>>()?;
+                    Ok(CustomGateApplication { gate, signals })
+                })
+                .collect::<Result<_, Error>>()?
+        }
+        None => vec![],
+    };
+
+    Ok(R1CSFile {
+        version: sections.version,
+        header,
+        r1cs,
+        wire_to_label,
+        custom_gates,
+        custom_gate_applications,
+    })
+}
+
+/// Reads and parses the `.r1cs` file at `path`.
+pub fn load_r1cs<F: FieldExt>(path: impl AsRef<Path>) -> Result<R1CSFile<F>, Error> {
+    read_r1cs(&fs::read(path)?)
+}
+
+impl<F: FieldExt> R1CSFile<F> {
+    /// Returns the R1CS instance, refusing circuits that rely on custom gates,
+    /// whose semantics are not part of the constraint matrices.
+    pub fn into_r1cs(self) -> Result<R1CS<F>, Error> {
+        if !self.custom_gate_applications.is_empty() {
+            return Err(Error::Unsupported(format!(
+                "{} custom gate applications",
+                self.custom_gate_applications.len()
+            )));
+        }
+        Ok(self.r1cs)
+    }
+}
+
+fn read_header(mut reader: Reader<'_>) -> Result<Header, Error> {
+    let field_size = reader.u32()?;
+    if field_size == 0 || field_size % 8 != 0 {
+        return Err(Error::Format(format!("field size {}", field_size)));
+    }
+    let prime = reader.bytes(field_size as usize)?.to_vec();
+
+    Ok(Header {
+        field_size,
+        prime,
+        n_wires: reader.u32()?,
+        n_pub_out: reader.u32()?,
+        n_pub_in: reader.u32()?,
+        n_prv_in: reader.u32()?,
+        n_labels: reader.u64()?,
+        n_constraints: reader.u32()?,
+    })
+}
+
+fn read_lc<F: FieldExt>(reader: &mut Reader<'_>, header: &Header) -> Result<LinearCombination<F>, Error> {
+    let n_terms = reader.u32()?;
+    (0..n_terms)
+        .map(|_| {
+            let wire = reader.u32()?;
+            if wire >= header.n_wires {
+                return Err(Error::Format(format!("wire {} out of range", wire)));
+            }
+            Ok((wire as usize, read_element(reader, header)?))
+        })
+        .collect()
+}
+
+fn read_element<F: FieldExt>(reader: &mut Reader<'_>, header: &Header) -> Result<F, Error> {
+    let bytes = reader.bytes(header.field_size as usize)?;
+    field::from_le_bytes(bytes).ok_or_else(|| Error::OutOfRange {
+        value: field::le_bytes_to_hex(bytes),
+        modulus: field::modulus_hex::<F>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_r1cs;
+    use crate::{error::Error, field};
+    use halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
+
+    fn section(out: &mut Vec<u8>, section_type: u32, body: &[u8]) {
+        out.extend(section_type.to_le_bytes());
+        out.extend((body.len() as u64).to_le_bytes());
+        out.extend(body);
+    }
+
+    // x * x = y with y a public output: wires [1, y, x]
+    fn square() -> Vec<u8> {
+        circuit([3, 1, 0, 1])
+    }
+
+    // The constraint of `square` under a header with the given nWires,
+    // nPubOut, nPubIn and nPrvIn.
+    fn circuit(counts: [u32; 4]) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend(32u32.to_le_bytes());
+        header.extend(field::modulus_le_bytes::<Fr>());
+        for n in counts {
+            header.extend(n.to_le_bytes());
+        }
+        header.extend(3u64.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+
+        let mut constraints = vec![];
+        for wire in [2u32, 2, 1] {
+            constraints.extend(1u32.to_le_bytes());
+            constraints.extend(wire.to_le_bytes());
+            constraints.extend(field::to_le_bytes(&Fr::one(), 32));
+        }
+
+        let mut labels = vec![];
+        for label in [0u64, 1, 2] {
+            labels.extend(label.to_le_bytes());
+        }
+
+        let mut out = b"r1cs".to_vec();
+        out.extend(1u32.to_le_bytes());
+        out.extend(3u32.to_le_bytes());
+        // Constraints may precede the header.
+        section(&mut out, 2, &constraints);
+        section(&mut out, 1, &header);
+        section(&mut out, 3, &labels);
+        out
+    }
+
+    #[test]
+    fn test_read_r1cs() {
+        let file = read_r1cs::<Fr>(&square()).unwrap();
+        assert_eq!(file.header.n_wires, 3);
+        assert_eq!(file.wire_to_label, vec![0, 1, 2]);
+
+        let r1cs = file.into_r1cs().unwrap();
+        assert_eq!(r1cs.num_inputs, 1);
+        assert_eq!(r1cs.num_witnesses, 1);
+        assert_eq!(r1cs.is_satisfied(&[Fr::one(), Fr::from(9), Fr::from(3)]), Ok(()));
+    }
+
+    #[test]
+    fn test_read_r1cs_wrong_field() {
+        assert!(matches!(read_r1cs::<Fp>(&square()), Err(Error::FieldMismatch { .. })));
+    }
+
+    #[test]
+    fn test_read_r1cs_truncated() {
+        let bytes = square();
+        assert!(matches!(read_r1cs::<Fr>(&bytes[..bytes.len() - 1]), Err(Error::Format(_))));
+    }
+
+    #[test]
+    fn test_read_r1cs_public_overflow() {
+        // nPubOut + nPubIn exceeds u32::MAX, and the 3 wires.
+        let bytes = circuit([3, u32::MAX, u32::MAX, 1]);
+        assert!(matches!(read_r1cs::<Fr>(&bytes), Err(Error::Format(_))));
+    }
+}
@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, fs, path::Path};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{error::Error, graph, matrix::R1CS};
+
+/// Signal names of a circom `.sym` file, indexed by wire id.
+///
+/// Each line of the file is `label,wire,component,name`, with a wire of `-1`
+/// for signals the compiler optimized away. Several signals may share a wire
+/// when one is assigned to another; the first listed names the wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<usize, String>,
+}
+
+impl Symbols {
+    /// The signal name of `wire`, if it has one.
+    pub fn name(&self, wire: usize) -> Option<&str> {
+        self.names.get(&wire).map(String::as_str)
+    }
+
+    /// Names every wire of `r1cs`, falling back to its position in `z`.
+    pub fn names<F: FieldExt>(&self, r1cs: &R1CS<F>) -> Vec<String> {
+        (0..r1cs.num_variables())
+            .map(|wire| match self.name(wire) {
+                Some(name) => name.to_string(),
+                None => graph::default_name(r1cs.num_inputs, wire),
+            })
+            .collect()
+    }
+}
+
+/// Parses the contents of a `.sym` file.
+pub fn read_sym(text: &str) -> Result<Symbols, Error> {
+    let mut names = BTreeMap::new();
+    for (line, row) in text.lines().enumerate() {
+        if row.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = row.splitn(4, ',').collect();
+        let (wire, name) = match fields.as_slice() {
+            [_, wire, _, name] => (wire.trim(), name.trim()),
+            _ => return Err(Error::Format(format!("sym line {}: expected 4 fields", line + 1))),
+        };
+        let wire: i64 = wire
+            .parse()
+            .map_err(|_| Error::Format(format!("sym line {}: wire {:?}", line + 1, wire)))?;
+        let wire = match usize::try_from(wire) {
+            Ok(wire) => wire,
+            Err(_) if wire == -1 => continue,
+            Err(_) => return Err(Error::Format(format!("sym line {}: wire {}", line + 1, wire))),
+        };
+
+        names.entry(wire).or_insert_with(|| name.to_string());
+    }
+    Ok(Symbols { names })
+}
+
+/// Reads and parses the `.sym` file at `path`.
+pub fn load_sym(path: impl AsRef<Path>) -> Result<Symbols, Error> {
+    read_sym(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_sym;
+    use crate::{error::Error, R1CS};
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_read_sym() {
+        let sym = "1,1,0,main.y\n2,2,0,main.x\n3,-1,1,main.sq.tmp\n4,2,1,main.sq.in\n";
+        let symbols = read_sym(sym).unwrap();
+        assert_eq!(symbols.name(1), Some("main.y"));
+        assert_eq!(symbols.name(2), Some("main.x"));
+        assert_eq!(symbols.name(3), None);
+
+        let r1cs = R1CS::<Fr>::new(1, 1);
+        assert_eq!(symbols.names(&r1cs), vec!["one", "main.y", "main.x"]);
+    }
+
+    #[test]
+    fn test_read_sym_malformed() {
+        assert!(matches!(read_sym("1,x,0,main.y"), Err(Error::Format(_))));
+        assert!(matches!(read_sym("1,1,main.y"), Err(Error::Format(_))));
+    }
+
+    #[test]
+    fn test_read_sym_sparse() {
+        let symbols = read_sym("1,99999999999999,0,x").unwrap();
+        assert_eq!(symbols.name(99999999999999), Some("x"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::{fmt, fs, path::Path};
+use halo2_proofs::arithmetic::FieldExt;
+use serde_json::Value as Json;
+use wasmi::{core::Trap, Engine, Instance, Linker, Module, Store};
+
+use super::json::{json_error, parse_element};
+use crate::{error::Error, field};
+
+/// A circom 2 witness calculator, the `.wasm` file next to the `.r1cs`,
+/// run in an embedded interpreter.
+///
+/// Input signals are looked up by the 64-bit FNV-1a hash of their name and
+/// set one field element at a time through the calculator's shared memory,
+/// as 32-bit little-endian words.
+pub struct WitnessCalculator {
+    store: Store<()>,
+    instance: Instance,
+}
+
+impl WitnessCalculator {
+    /// Instantiates the witness calculator in `wasm`.
+    pub fn new(wasm: &[u8]) -> Result<Self, Error> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm).map_err(wasm_error)?;
+        let mut store = Store::new(&engine, ());
+
+        let mut linker = Linker::<()>::new(&engine);
+        linker
+            .func_wrap("runtime", "exceptionHandler", |code: i32| -> Result<(), Trap> {
+                Err(Trap::new(exception(code)))
+            })
+            .and_then(|linker| linker.func_wrap("runtime", "printErrorMessage", || {}))
+            .and_then(|linker| linker.func_wrap("runtime", "writeBufferMessage", || {}))
+            .and_then(|linker| linker.func_wrap("runtime", "showSharedRWMemory", || {}))
+            .map_err(wasm_error)?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(wasm_error)?;
+
+        let mut calculator = WitnessCalculator { store, instance };
+        let version = calculator.call::<(), i32>("getVersion", ())?;
+        if version != 2 {
+            return Err(Error::Unsupported(format!("witness calculator version {}", version)));
+        }
+        Ok(calculator)
+    }
+
+    /// Reads the `.wasm` file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::new(&fs::read(path)?)
+    }
+
+    /// Computes the witness vector `z` for `input`, a JSON object mapping
+    /// input signal names to decimal strings, integers or nested arrays of
+    /// them, as accepted by snarkjs.
+    pub fn calculate<F: FieldExt>(&mut self, input: &[u8]) -> Result<Vec<F>, Error> {
+        let input: serde_json::Map<String, Json> = serde_json::from_slice(input).map_err(json_error)?;
+
+        let n32 = self.call::<(), i32>("getFieldNumLen32", ())? as usize;
+        self.call::<(), ()>("getRawPrime", ())?;
+        let prime = self.read_shared(n32)?;
+        field::check_modulus::<F>(&prime)?;
+
+        self.call::<i32, ()>("init", 1)?;
+        for (name, value) in input.iter() {
+            let hash = fnv1a(name);
+            let (msb, lsb) = ((hash >> 32) as i32, hash as i32);
+
+            let mut values = vec![];
+            flatten::<F>(name, value, &mut values)?;
+            let size = self.call::<(i32, i32), i32>("getInputSignalSize", (msb, lsb))?;
+            if size < 0 {
+                return Err(Error::Format(format!("no input signal {:?}", name)));
+            }
+            if values.len() != size as usize {
+                return Err(Error::Format(format!(
+                    "input signal {:?} has {} values, expected {}",
+                    name,
+                    values.len(),
+                    size
+                )));
+            }
+
+            for (i, value) in values.iter().enumerate() {
+                self.write_shared(&field::to_le_bytes(value, n32 * 4))?;
+                self.call::<(i32, i32, i32), ()>("setInputSignal", (msb, lsb, i as i32))?;
+            }
+        }
+
+        let n_witness = self.call::<(), i32>("getWitnessSize", ())?;
+        (0..n_witness)
+            .map(|i| {
+                self.call::<i32, ()>("getWitness", i)?;
+                let bytes = self.read_shared(n32)?;
+                field::from_le_bytes(&bytes).ok_or_else(|| Error::OutOfRange {
+                    value: field::le_bytes_to_hex(&bytes),
+                    modulus: field::modulus_hex::<F>(),
+                })
+            })
+            .collect()
+    }
+
+    fn call<P: wasmi::WasmParams, R: wasmi::WasmResults>(&mut self, name: &str, params: P) -> Result<R, Error> {
+        let func = self
+            .instance
+            .get_typed_func::<P, R>(&self.store, name)
+            .map_err(|err| Error::Format(format!("witness calculator export {}: {}", name, err)))?;
+        func.call(&mut self.store, params).map_err(wasm_error)
+    }
+
+    /// Reads `n32` words of shared memory as little-endian bytes.
+    fn read_shared(&mut self, n32: usize) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(n32 * 4);
+        for i in 0..n32 {
+            let word = self.call::<i32, i32>("readSharedRWMemory", i as i32)?;
+            bytes.extend((word as u32).to_le_bytes());
+        }
+        Ok(bytes)
+    }
+
+    fn write_shared(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        for (i, word) in bytes.chunks(4).enumerate() {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            self.call::<(i32, i32), ()>("writeSharedRWMemory", (i as i32, word as i32))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the witness calculator at `wasm` on the input JSON file at `input`.
+pub fn calculate_witness<F: FieldExt>(wasm: impl AsRef<Path>, input: impl AsRef<Path>) -> Result<Vec<F>, Error> {
+    WitnessCalculator::load(wasm)?.calculate(&fs::read(input)?)
+}
+
+/// Appends the field elements of an input signal value in row-major order.
+fn flatten<F: FieldExt>(name: &str, value: &Json, out: &mut Vec<F>) -> Result<(), Error> {
+    match value {
+        Json::Array(values) => {
+            for value in values {
+                flatten(name, value, out)?;
+            }
+        }
+        Json::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => out.push(F::from(n)),
+            (None, Some(n)) => out.push(-F::from(n.unsigned_abs())),
+            _ => return Err(Error::Format(format!("input signal {:?}: {} is not an integer", name, n))),
+        },
+        Json::String(s) => match s.strip_prefix('-') {
+            Some(s) => out.push(-parse_element::<F>(s)?),
+            None => out.push(parse_element(s)?),
+        },
+        _ => return Err(Error::Format(format!("input signal {:?}: unexpected {}", name, value))),
+    }
+    Ok(())
+}
+
+/// The 64-bit FNV-1a hash circom uses to look up signals by name.
+fn fnv1a(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn exception(code: i32) -> String {
+    match code {
+        1 => "signal not found".to_string(),
+        2 => "too many values for input signal".to_string(),
+        3 => "signal already set".to_string(),
+        4 => "assert failed".to_string(),
+        5 => "not enough memory".to_string(),
+        6 => "input signal array access out of bounds".to_string(),
+        _ => format!("exception {}", code),
+    }
+}
+
+fn wasm_error(err: impl fmt::Display) -> Error {
+    Error::WitnessCalculator(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten, fnv1a, WitnessCalculator};
+    use crate::error::Error;
+    use halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
+
+    // A calculator over BN254 for wires [1, y, x] with the single input
+    // signal `x`, copied to `y`. Shared memory is the 8 words at 0, the input
+    // is kept at 64, and `fnv1a("x")` is 0xaf63f54c86021707.
+    const COPY: &str = r#"(module
+        (import "runtime" "exceptionHandler" (func $exception (param i32)))
+        (memory 1)
+        (data (i32.const 128) "\01\00\00\f0\93\f5\e1\43\91\70\b9\79\48\e8\33\28\5d\58\81\81\b6\45\50\b8\29\a0\31\e1\72\4e\64\30")
+        (data (i32.const 192) "\01")
+        (global $initialized (mut i32) (i32.const 0))
+
+        (func $copy (param $from i32) (param $to i32)
+            (local $i i32)
+            (loop $words
+                (i32.store (i32.add (local.get $to) (local.get $i)) (i32.load (i32.add (local.get $from) (local.get $i))))
+                (local.set $i (i32.add (local.get $i) (i32.const 4)))
+                (br_if $words (i32.lt_u (local.get $i) (i32.const 32)))))
+        (func $is_x (param i32 i32) (result i32)
+            (i32.and (i32.eq (local.get 0) (i32.const 0xaf63f54c)) (i32.eq (local.get 1) (i32.const 0x86021707))))
+
+        (func (export "getVersion") (result i32) (i32.const 2))
+        (func (export "getFieldNumLen32") (result i32) (i32.const 8))
+        (func (export "getRawPrime") (call $copy (i32.const 128) (i32.const 0)))
+        (func (export "readSharedRWMemory") (param i32) (result i32)
+            (i32.load (i32.shl (local.get 0) (i32.const 2))))
+        (func (export "writeSharedRWMemory") (param i32 i32)
+            (i32.store (i32.shl (local.get 0) (i32.const 2)) (local.get 1)))
+        (func (export "init") (param i32) (global.set $initialized (i32.const 1)))
+        (func (export "getInputSignalSize") (param i32 i32) (result i32)
+            (select (i32.const 1) (i32.const -1) (call $is_x (local.get 0) (local.get 1))))
+        (func (export "setInputSignal") (param i32 i32 i32)
+            (if (i32.eqz (global.get $initialized)) (then (call $exception (i32.const 4))))
+            (call $copy (i32.const 0) (i32.const 64)))
+        (func (export "getWitnessSize") (result i32) (i32.const 3))
+        (func (export "getWitness") (param i32)
+            (call $copy (select (i32.const 192) (i32.const 64) (i32.eqz (local.get 0))) (i32.const 0))))"#;
+
+    fn calculator() -> WitnessCalculator {
+        WitnessCalculator::new(&wat::parse_str(COPY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_calculate() {
+        let z = calculator().calculate::<Fr>(br#"{"x": "3"}"#).unwrap();
+        assert_eq!(z, vec![Fr::one(), Fr::from(3), Fr::from(3)]);
+        let z = calculator().calculate::<Fr>(br#"{"x": -1}"#).unwrap();
+        assert_eq!(z, vec![Fr::one(), -Fr::one(), -Fr::one()]);
+    }
+
+    #[test]
+    fn test_calculate_errors() {
+        assert!(matches!(calculator().calculate::<Fp>(br#"{"x": 3}"#), Err(Error::FieldMismatch { .. })));
+        assert!(matches!(calculator().calculate::<Fr>(br#"{"x": [3, 4]}"#), Err(Error::Format(_))));
+        assert!(matches!(calculator().calculate::<Fr>(br#"{"y": 3}"#), Err(Error::Format(_))));
+        assert!(matches!(calculator().calculate::<Fr>(br#"[3]"#), Err(Error::Format(_))));
+    }
+
+    #[test]
+    fn test_version() {
+        let v1 = COPY.replace("(result i32) (i32.const 2)", "(result i32) (i32.const 1)");
+        assert!(matches!(
+            WitnessCalculator::new(&wat::parse_str(v1).unwrap()),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let input: serde_json::Value = serde_json::from_str(r#"[["1", 2], [-3, "-4"]]"#).unwrap();
+        let mut values = vec![];
+        flatten::<Fr>("in", &input, &mut values).unwrap();
+        assert_eq!(values, vec![Fr::from(1), Fr::from(2), -Fr::from(3), -Fr::from(4)]);
+        assert!(flatten::<Fr>("in", &serde_json::json!(1.5), &mut values).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fs, path::Path};
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::Sections;
+use crate::{error::Error, field};
+
+const HEADER: u32 = 1;
+const WITNESS: u32 = 2;
+
+/// Parses a `.wtns` file into the witness vector `z`, rejecting files
+/// produced for another prime.
+///
+/// The witness is indexed by circom wire id, so it can be passed as is to
+/// [`R1CSCircuit::new`](crate::R1CSCircuit::new) with the matching `.r1cs`.
+pub fn read_wtns<F: FieldExt>(bytes: &[u8]) -> Result<Vec<F>, Error> {
+    let sections = Sections::parse(bytes, b"wtns")?;
+    if sections.version != 1 && sections.version != 2 {
+        return Err(Error::Unsupported(format!("wtns version {}", sections.version)));
+    }
+
+    let mut header = sections.require(HEADER, "header")?;
+    let field_size = header.u32()? as usize;
+    if field_size == 0 || field_size % 8 != 0 {
+        return Err(Error::Format(format!("field size {}", field_size)));
+    }
+    let prime = header.bytes(field_size)?;
+    field::check_modulus::<F>(prime)?;
+    let n_witness = header.u32()?;
+
+    let mut reader = sections.require(WITNESS, "witness")?;
+    (0..n_witness)
+        .map(|_| {
+            let bytes = reader.bytes(field_size)?;
+            field::from_le_bytes(bytes).ok_or_else(|| Error::OutOfRange {
+                value: field::le_bytes_to_hex(bytes),
+                modulus: field::modulus_hex::<F>(),
+            })
+        })
+        .collect()
+}
+
+/// Reads and parses the `.wtns` file at `path`.
+pub fn load_wtns<F: FieldExt>(path: impl AsRef<Path>) -> Result<Vec<F>, Error> {
+    read_wtns(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_wtns;
+    use crate::{error::Error, field, fixtures::square, R1CSCircuit};
+    use halo2_proofs::{dev::MockProver, halo2curves::{bn256::Fr, pasta::Fp}};
+
+    fn wtns(z: &[u64]) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend(32u32.to_le_bytes());
+        header.extend(field::modulus_le_bytes::<Fr>());
+        header.extend((z.len() as u32).to_le_bytes());
+
+        let mut witness = vec![];
+        for v in z {
+            witness.extend(field::to_le_bytes(&Fr::from(*v), 32));
+        }
+
+        let mut out = b"wtns".to_vec();
+        out.extend(2u32.to_le_bytes());
+        out.extend(2u32.to_le_bytes());
+        for (section_type, body) in [(1u32, header), (2, witness)] {
+            out.extend(section_type.to_le_bytes());
+            out.extend((body.len() as u64).to_le_bytes());
+            out.extend(body);
+        }
+        out
+    }
+
+    #[test]
+    fn test_read_wtns() {
+        let z = read_wtns::<Fr>(&wtns(&[1, 9, 3])).unwrap();
+        assert_eq!(z, vec![Fr::one(), Fr::from(9), Fr::from(3)]);
+
+        let (r1cs, _) = square();
+        let circuit = R1CSCircuit::<Fr>::new(r1cs, z).unwrap();
+        MockProver::run(6, &circuit, vec![vec![Fr::from(9)]]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn test_read_wtns_wrong_field() {
+        assert!(matches!(read_wtns::<Fp>(&wtns(&[1])), Err(Error::FieldMismatch { .. })));
+    }
+}
@@ -0,0 +1,249 @@
+//! Translation of `MockProver` failures back to R1CS constraints.
+//!
+//! halo2 reports failures by gate and region offset; this maps each one to
+//! the R1CS constraint laid out at that offset, the part of it that failed,
+//! the names of the wires it uses and its evaluated `A·z`, `B·z` and `C·z`.
+
+use std::fmt;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+};
+
+use crate::{
+    matrix::evaluate,
+    r1cs::{R1CSChip, R1CSCircuit},
+};
+
+/// The row of a constraint's layout a failure occurred in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    /// Evaluation of `A·z`.
+    A,
+    /// Evaluation of `B·z`.
+    B,
+    /// Evaluation of `C·z`.
+    C,
+    /// The product row `(A·z) * (B·z) = C·z`.
+    Product,
+}
+
+/// A `MockProver` failure in terms of the R1CS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic<F> {
+    /// The failure as reported by halo2.
+    pub failure: String,
+    /// Index of the R1CS constraint, if the failure is inside one.
+    pub constraint: Option<(usize, Part)>,
+    /// Wires of the constraint, or of the witness row, with their names.
+    pub wires: Vec<(usize, String)>,
+    /// `(A·z, B·z, C·z)` of the constraint, if the witness is known.
+    pub values: Option<(F, F, F)>,
+}
+
+impl<F: FieldExt> fmt::Display for Diagnostic<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.constraint {
+            Some((index, part)) => writeln!(f, "R1CS constraint {} ({:?}):", index, part)?,
+            None => writeln!(f, "outside any R1CS constraint:")?,
+        }
+        writeln!(f, "  {}", self.failure)?;
+        if !self.wires.is_empty() {
+            let wires: Vec<_> = self.wires.iter().map(|(_, name)| name.as_str()).collect();
+            writeln!(f, "  wires: {}", wires.join(", "))?;
+        }
+        if let Some((a, b, c)) = self.values {
+            writeln!(f, "  A·z = {:?}", a)?;
+            writeln!(f, "  B·z = {:?}", b)?;
+            writeln!(f, "  C·z = {:?}", c)?;
+            writeln!(f, "  (A·z)(B·z) - C·z = {:?}", a * b - c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `MockProver` on `circuit` with the public inputs of its witness and
+/// explains any failure, naming wires with `names`.
+pub fn verify<F: FieldExt, const WIDTH: usize>(
+    circuit: &R1CSCircuit<F, WIDTH>,
+    k: u32,
+    names: impl Fn(usize) -> String,
+) -> Result<(), Vec<Diagnostic<F>>> {
+    let z = circuit.known_witness();
+    let r1cs = circuit.r1cs();
+    let public_inputs = z
+        .as_deref()
+        .and_then(|z| r1cs.public_inputs(z))
+        .map_or_else(|| vec![F::zero(); r1cs.num_inputs], <[F]>::to_vec);
+
+    let prover = MockProver::run(k, circuit, vec![public_inputs]).map_err(|err| {
+        vec![Diagnostic {
+            failure: err.to_string(),
+            constraint: None,
+            wires: vec![],
+            values: None,
+        }]
+    })?;
+    prover
+        .verify()
+        .map_err(|failures| explain(circuit, &failures, names))
+}
+
+/// Explains `failures` from running `MockProver` on `circuit`.
+pub fn explain<F: FieldExt, const WIDTH: usize>(
+    circuit: &R1CSCircuit<F, WIDTH>,
+    failures: &[VerifyFailure],
+    names: impl Fn(usize) -> String,
+) -> Vec<Diagnostic<F>> {
+    let r1cs = circuit.r1cs();
+    let z = circuit.known_witness();
+
+    failures
+        .iter()
+        .map(|failure| {
+            let location = match failure {
+                VerifyFailure::CellNotAssigned { region, offset, .. } => {
+                    usize::try_from(*offset).ok().map(|offset| (region, offset))
+                }
+                VerifyFailure::ConstraintNotSatisfied { location, .. }
+                | VerifyFailure::Lookup { location, .. }
+                | VerifyFailure::Permutation { location, .. } => match location {
+                    FailureLocation::InRegion { region, offset } => Some((region, *offset)),
+                    FailureLocation::OutsideRegion { .. } => None,
+                },
+                _ => None,
+            };
+
+            let mut diagnostic = Diagnostic {
+                failure: failure.to_string(),
+                constraint: None,
+                wires: vec![],
+                values: None,
+            };
+            let (region, offset) = match location {
+                Some(location) => location,
+                None => return diagnostic,
+            };
+
+            match locate::<F, WIDTH>(circuit, region, offset) {
+                Located::Witness(wires) => {
+                    diagnostic.wires = wires.map(|wire| (wire, names(wire))).collect();
+                }
+                Located::Constraint(index, part) => {
+                    let (a, b, c) = r1cs.constraint(index);
+                    let mut wires: Vec<usize> = a.iter().chain(b).chain(c).map(|(wire, _)| *wire).collect();
+                    wires.sort_unstable();
+                    wires.dedup();
+
+                    diagnostic.constraint = Some((index, part));
+                    diagnostic.wires = wires.into_iter().map(|wire| (wire, names(wire))).collect();
+                    diagnostic.values = z.as_ref().and_then(|z| {
+                        Some((evaluate(a, z).ok()?, evaluate(b, z).ok()?, evaluate(c, z).ok()?))
+                    });
+                }
+                Located::Unknown => {}
+            }
+            diagnostic
+        })
+        .collect()
+}
+
+/// Names wires as the circuit does, by signal name if it was given any and
+/// otherwise by position in `z`.
+pub fn default_names<F: FieldExt, const WIDTH: usize>(
+    circuit: &R1CSCircuit<F, WIDTH>,
+) -> impl Fn(usize) -> String + '_ {
+    move |wire| circuit.name(wire)
+}
+
+enum Located {
+    Witness(std::ops::Range<usize>),
+    Constraint(usize, Part),
+    Unknown,
+}
+
+/// Finds what `R1CSCircuit::synthesize` laid out at `offset` in `region`.
+fn locate<F: FieldExt, const WIDTH: usize>(
+    circuit: &R1CSCircuit<F, WIDTH>,
+    region: &metadata::Region,
+    offset: usize,
+) -> Located {
+    let r1cs = circuit.r1cs();
+
+    // `metadata::Region` only exposes its name through `Display`, as
+    // "Region <index> ('<name>')".
+    let region = region.to_string();
+    let name = match (region.find("('"), region.rfind("')")) {
+        (Some(start), Some(end)) if start + 2 <= end => &region[start + 2..end],
+        _ => return Located::Unknown,
+    };
+
+    if name == "witness" {
+        let start = (offset * WIDTH).min(r1cs.num_variables());
+        let end = ((offset + 1) * WIDTH).min(r1cs.num_variables());
+        return Located::Witness(start..end);
+    }
+
+    let first = match name
+        .strip_prefix("constraints ")
+        .and_then(|rows| rows.split("..").next())
+        .and_then(|start| start.parse::<usize>().ok())
+    {
+        Some(first) => first,
+        None => return Located::Unknown,
+    };
+
+    let mut start = 0;
+    for index in first..r1cs.num_constraints() {
+        let (a, b, c) = r1cs.constraint(index);
+        let rows = [a.len(), b.len(), c.len()].map(R1CSChip::<F, WIDTH>::lc_rows);
+        if offset < start + R1CSChip::<F, WIDTH>::constraint_rows(a.len(), b.len(), c.len()) {
+            let mut part_start = start;
+            for (part, rows) in [Part::A, Part::B, Part::C].into_iter().zip(rows) {
+                if offset < part_start + rows {
+                    return Located::Constraint(index, part);
+                }
+                part_start += rows;
+            }
+            return Located::Constraint(index, Part::Product);
+        }
+        start += R1CSChip::<F, WIDTH>::constraint_rows(a.len(), b.len(), c.len());
+    }
+    Located::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_names, verify, Part};
+    use crate::fixtures;
+    use crate::R1CSCircuit;
+    use halo2_proofs::halo2curves::bn256::Fr as Fp;
+
+    fn cubic(z: [u64; 6]) -> R1CSCircuit<Fp> {
+        R1CSCircuit::new(fixtures::cubic(), z.map(Fp::from).to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_ok() {
+        let circuit = cubic([1, 35, 3, 9, 27, 30]);
+        assert_eq!(verify(&circuit, circuit.min_k(), default_names(&circuit)), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_maps_constraint() {
+        // x*x*x is wrong, so constraints 1 and 2 fail.
+        for chunk_size in [1, 4] {
+            let circuit = cubic([1, 35, 3, 9, 28, 30]).with_chunk_size(chunk_size);
+            let diagnostics = verify(&circuit, circuit.min_k(), |wire| format!("w{}", wire)).unwrap_err();
+
+            let constraints: Vec<_> = diagnostics.iter().filter_map(|d| d.constraint).collect();
+            assert!(constraints.contains(&(1, Part::Product)));
+            assert!(constraints.iter().all(|(index, _)| *index == 1 || *index == 2));
+
+            let product = diagnostics.iter().find(|d| d.constraint == Some((1, Part::Product))).unwrap();
+            assert_eq!(product.values, Some((Fp::from(9), Fp::from(3), Fp::from(28))));
+            assert!(product.wires.iter().any(|(_, name)| name == "w4"));
+            assert!(product.to_string().contains("R1CS constraint 1"));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fmt, io};
+use halo2_proofs::plonk;
+
+use crate::{matrix::Unsatisfied, r1cs::RowBudget};
+
+/// Errors from loading R1CS instances and witnesses, and from proving them.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The input is malformed.
+    Format(String),
+    /// The input is well-formed but uses a feature this crate cannot prove.
+    Unsupported(String),
+    /// The input was produced over a different prime than the target field,
+    /// both described by [`field::describe_prime`](crate::field::describe_prime).
+    FieldMismatch { expected: String, found: String },
+    /// A value in the input is not smaller than the modulus of the target field.
+    OutOfRange { value: String, modulus: String },
+    /// The circom witness calculator failed to run.
+    WitnessCalculator(String),
+    /// The witness does not satisfy the R1CS.
+    Unsatisfied(Unsatisfied),
+    /// The circuit does not fit in `2^k` rows.
+    NotEnoughRows { k: u32, budget: RowBudget },
+    /// A key or proof belongs to another R1CS, proving system or size.
+    Mismatch(String),
+    /// Key generation, proving or verification failed.
+    Plonk(plonk::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Format(msg) => write!(f, "invalid format: {}", msg),
+            Self::Unsupported(msg) => write!(f, "unsupported: {}", msg),
+            Self::FieldMismatch { expected, found } => {
+                write!(f, "field mismatch: the circuit is over {}, but the input is over {}", expected, found)
+            }
+            Self::OutOfRange { value, modulus } => {
+                write!(f, "{} is out of range for field with modulus {}", value, modulus)
+            }
+            Self::WitnessCalculator(msg) => write!(f, "witness calculator: {}", msg),
+            Self::Unsatisfied(err) => write!(f, "{}", err),
+            Self::NotEnoughRows { k, budget } => {
+                write!(f, "k = {} gives {} rows, but the circuit needs {}", k, 1u64 << k, budget)
+            }
+            Self::Mismatch(msg) => write!(f, "mismatch: {}", msg),
+            Self::Plonk(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Unsatisfied(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Unsatisfied> for Error {
+    fn from(err: Unsatisfied) -> Self {
+        Self::Unsatisfied(err)
+    }
+}
+
+impl From<plonk::Error> for Error {
+    fn from(err: plonk::Error) -> Self {
+        Self::Plonk(err)
+    }
+}
@@ -0,0 +1,156 @@
+//! EVM verifiers for KZG proofs over BN254.
+//!
+//! The verifier is generated by `snark-verifier` from a verifying key made
+//! by [`prover::keygen`](crate::prover::keygen). It is Yul rather than
+//! Solidity source, and is compiled to EVM bytecode with `solc --yul`. It
+//! replays the transcript with Keccak-256 rather than Blake2b, so proofs
+//! checked on-chain are created with [`prove`] instead of
+//! [`prover::prove`](crate::prover::prove), and submitted with the calldata
+//! of [`encode_calldata`]. The public inputs are the single instance column,
+//! `z[1..=num_inputs]`.
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{create_proof, verify_proof, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::TranscriptWriterBuffer,
+};
+use rand_core::OsRng;
+use snark_verifier::{
+    loader::{
+        evm::{self, EvmLoader},
+        native::NativeLoader,
+    },
+    pcs::kzg::{Bdfg21, KzgAs, KzgDecidingKey},
+    system::halo2::{compile, transcript::evm::EvmTranscript, Config},
+    verifier::{plonk::PlonkVerifier, SnarkVerifier},
+};
+
+use crate::{error::Error, matrix::R1CS, r1cs::R1CSCircuit};
+
+// SHPLONK, as in `prover`, is BDFG21.
+type Verifier = PlonkVerifier<KzgAs<Bn256, Bdfg21>>;
+
+/// Renders the Yul source of a verifier contract for `vk`, the verifying key
+/// of `r1cs`.
+pub fn render_yul(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    r1cs: &R1CS<Fr>,
+) -> Result<String, Error> {
+    let num_instance = vec![r1cs.num_inputs];
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(num_instance.clone()),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(num_instance);
+    Verifier::read_proof(&dk, &protocol, &instances, &mut transcript)
+        .and_then(|proof| Verifier::verify(&dk, &protocol, &instances, &proof))
+        .map_err(|err| Error::Format(format!("rendering verifier: {:?}", err)))?;
+    Ok(loader.yul_code())
+}
+
+/// Proves that the witness of `circuit` satisfies its R1CS with a Keccak-256
+/// transcript, for the contract of [`render_yul`].
+pub fn prove<const WIDTH: usize>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: R1CSCircuit<Fr, WIDTH>,
+) -> Result<Vec<u8>, Error> {
+    let z = circuit
+        .known_witness()
+        .ok_or_else(|| Error::Unsupported("proving a circuit without a witness".to_string()))?;
+    circuit.r1cs().is_satisfied(&z)?;
+    let public_inputs = circuit.r1cs().public_inputs(&z).expect("z has every variable").to_vec();
+    let mut transcript = EvmTranscript::<_, NativeLoader, _, _>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[&public_inputs]],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Verifies a proof created by [`prove`] natively, as the contract would.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    public_inputs: &[Fr],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let mut transcript = EvmTranscript::<_, NativeLoader, _, _>::new(proof);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[&[public_inputs]],
+        &mut transcript,
+    )?;
+    Ok(())
+}
+
+/// The calldata of a call to the verifier: the public inputs as big-endian
+/// words followed by the proof.
+pub fn encode_calldata(proof: &[u8], public_inputs: &[Fr]) -> Vec<u8> {
+    evm::encode_calldata(&[public_inputs.to_vec()], proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::square;
+    use crate::prover;
+    use snark_verifier::loader::evm::{compile_yul, deploy_and_call};
+
+    #[test]
+    fn test_prove_and_verify() {
+        let (r1cs, z) = square();
+        let circuit = R1CSCircuit::<Fr>::new(r1cs, z).unwrap();
+        let params = prover::setup_for(&circuit);
+        let pk = prover::keygen(&params, &circuit).unwrap();
+
+        let proof = prove(&params, &pk, circuit).unwrap();
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(9)], &proof).is_ok());
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(10)], &proof).is_err());
+    }
+
+    /// Compiles the verifier with `solc` and runs it in revm.
+    #[test]
+    fn test_deploy() {
+        let (r1cs, z) = square();
+        let circuit = R1CSCircuit::<Fr>::new(r1cs.clone(), z).unwrap();
+        let params = prover::setup_for(&circuit);
+        let pk = prover::keygen(&params, &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit).unwrap();
+
+        let code = compile_yul(&render_yul(&params, pk.get_vk(), &r1cs).unwrap());
+        let accepts = |proof: &[u8], public_inputs: &[Fr]| {
+            deploy_and_call(code.clone(), encode_calldata(proof, public_inputs)).is_ok()
+        };
+
+        assert!(accepts(&proof, &[Fr::from(9)]));
+        assert!(!accepts(&proof, &[Fr::from(10)]));
+
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        assert!(!accepts(&tampered, &[Fr::from(9)]));
+    }
+}
@@ -0,0 +1,226 @@
+//! Conversions between halo2 field elements and the little-endian byte
+//! encodings used by circom and snarkjs.
+//!
+//! These assume `F::Repr` is the little-endian canonical encoding, which holds
+//! for the `bn256` and `pasta` scalar fields.
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::error::Error;
+
+/// Primes circom can compile for, by `circom --prime` name and field.
+const KNOWN_PRIMES: [(&str, &str); 5] = [
+    (
+        "bn128, the BN254 scalar field",
+        "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+    ),
+    (
+        "bls12381, the BLS12-381 scalar field",
+        "52435875175126190479447740508185965837690552500527637822603658699938581184513",
+    ),
+    ("goldilocks", "18446744069414584321"),
+    (
+        "pallas, the Pallas base field",
+        "28948022309329048855892746252171976963363056481941560715954676764349967630337",
+    ),
+    (
+        "vesta, the Vesta base field",
+        "28948022309329048855892746252171976963363056481941647379679742748393362948097",
+    ),
+];
+
+/// Byte length of the canonical encoding of `F`.
+pub fn repr_len<F: FieldExt>() -> usize {
+    F::Repr::default().as_ref().len()
+}
+
+/// The modulus of `F`, little-endian, padded to [`repr_len`].
+pub fn modulus_le_bytes<F: FieldExt>() -> Vec<u8> {
+    let hex = F::MODULUS.trim_start_matches("0x");
+    let digits: Vec<u8> = hex
+        .bytes()
+        .rev()
+        .map(|c| (c as char).to_digit(16).expect("FieldExt::MODULUS is hex") as u8)
+        .collect();
+
+    let mut bytes: Vec<u8> = digits
+        .chunks(2)
+        .map(|pair| pair[0] | pair.get(1).map_or(0, |hi| hi << 4))
+        .collect();
+    bytes.resize(repr_len::<F>().max(bytes.len()), 0);
+    bytes
+}
+
+/// Returns true if `prime`, little-endian, equals the modulus of `F`.
+pub fn is_modulus<F: FieldExt>(prime: &[u8]) -> bool {
+    trim(prime) == trim(&modulus_le_bytes::<F>())
+}
+
+/// Rejects inputs declaring the little-endian `prime` unless it is the
+/// modulus of `F`.
+pub fn check_modulus<F: FieldExt>(prime: &[u8]) -> Result<(), Error> {
+    if !is_modulus::<F>(prime) {
+        return Err(Error::FieldMismatch {
+            expected: describe_prime(&modulus_le_bytes::<F>()),
+            found: describe_prime(prime),
+        });
+    }
+    Ok(())
+}
+
+/// Formats a little-endian prime in hex, naming it if circom knows it.
+pub fn describe_prime(prime: &[u8]) -> String {
+    let name = KNOWN_PRIMES.iter().find(|(_, decimal)| {
+        decimal_to_le_bytes(decimal).is_some_and(|known| trim(&known) == trim(prime))
+    });
+    match name {
+        Some((name, _)) => format!("{} ({})", le_bytes_to_hex(prime), name),
+        None => le_bytes_to_hex(prime),
+    }
+}
+
+/// Parses a canonical little-endian encoding, rejecting values `>= F::MODULUS`.
+pub fn from_le_bytes<F: FieldExt>(bytes: &[u8]) -> Option<F> {
+    let bytes = trim(bytes);
+    let mut repr = F::Repr::default();
+    if bytes.len() > repr.as_ref().len() {
+        return None;
+    }
+    repr.as_mut()[..bytes.len()].copy_from_slice(bytes);
+    F::from_repr(repr).into()
+}
+
+/// Canonical little-endian encoding of `value`, padded with zeros to `len` bytes.
+pub fn to_le_bytes<F: FieldExt>(value: &F, len: usize) -> Vec<u8> {
+    let mut bytes = value.to_repr().as_ref().to_vec();
+    bytes.resize(len, 0);
+    bytes
+}
+
+/// Formats the modulus of `F` as a hex string for error messages.
+pub fn modulus_hex<F: FieldExt>() -> String {
+    F::MODULUS.to_string()
+}
+
+/// Formats a little-endian integer as a `0x`-prefixed hex string.
+pub fn le_bytes_to_hex(bytes: &[u8]) -> String {
+    let digits: String = trim(bytes).iter().rev().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", if digits.is_empty() { "0" } else { &digits })
+}
+
+/// Parses an unsigned decimal integer into little-endian bytes.
+pub fn decimal_to_le_bytes(s: &str) -> Option<Vec<u8>> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut bytes: Vec<u8> = vec![];
+    for c in s.bytes() {
+        let mut carry = (c - b'0') as u32;
+        for b in bytes.iter_mut() {
+            let v = *b as u32 * 10 + carry;
+            *b = v as u8;
+            carry = v >> 8;
+        }
+        if carry > 0 {
+            bytes.push(carry as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Formats a little-endian integer in decimal.
+pub fn le_bytes_to_decimal(bytes: &[u8]) -> String {
+    let mut n = trim(bytes).to_vec();
+    let mut digits = vec![];
+    while !n.is_empty() {
+        let mut rem = 0u32;
+        for b in n.iter_mut().rev() {
+            let v = (rem << 8) | *b as u32;
+            *b = (v / 10) as u8;
+            rem = v % 10;
+        }
+        digits.push(b'0' + rem as u8);
+        n.truncate(trim(&n).len());
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Parses a decimal string, rejecting values `>= F::MODULUS`.
+pub fn from_decimal<F: FieldExt>(s: &str) -> Option<F> {
+    decimal_to_le_bytes(s).and_then(|bytes| from_le_bytes(&bytes))
+}
+
+/// Formats `value` in decimal, as snarkjs does.
+pub fn to_decimal<F: FieldExt>(value: &F) -> String {
+    le_bytes_to_decimal(value.to_repr().as_ref())
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
+
+    #[test]
+    fn test_modulus() {
+        let modulus = modulus_le_bytes::<Fr>();
+        assert_eq!(modulus.len(), 32);
+        assert_eq!(modulus[0], 0x01);
+        assert_eq!(modulus[31], 0x30);
+        assert!(is_modulus::<Fr>(&modulus));
+        assert!(!is_modulus::<Fp>(&modulus));
+        assert_eq!(le_bytes_to_hex(&modulus), modulus_hex::<Fr>());
+    }
+
+    #[test]
+    fn test_check_modulus() {
+        assert!(check_modulus::<Fr>(&modulus_le_bytes::<Fr>()).is_ok());
+
+        let bls = decimal_to_le_bytes(
+            "52435875175126190479447740508185965837690552500527637822603658699938581184513",
+        )
+        .unwrap();
+        match check_modulus::<Fr>(&bls) {
+            Err(Error::FieldMismatch { expected, found }) => {
+                assert!(expected.contains("BN254"));
+                assert!(found.contains("BLS12-381"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let goldilocks = 0xffffffff00000001u64.to_le_bytes();
+        assert!(matches!(check_modulus::<Fr>(&goldilocks), Err(Error::FieldMismatch { .. })));
+        assert!(describe_prime(&modulus_le_bytes::<Fp>()).contains("pallas"));
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        let x = Fr::from(0x1234);
+        assert_eq!(from_le_bytes::<Fr>(&to_le_bytes(&x, 40)), Some(x));
+        assert_eq!(from_le_bytes::<Fr>(&[0x34, 0x12]), Some(x));
+        assert_eq!(from_le_bytes::<Fr>(&modulus_le_bytes::<Fr>()), None);
+    }
+
+    #[test]
+    fn test_decimal() {
+        let modulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        assert_eq!(le_bytes_to_decimal(&modulus_le_bytes::<Fr>()), modulus);
+        assert_eq!(decimal_to_le_bytes(modulus), Some(modulus_le_bytes::<Fr>()));
+
+        let minus_one = -Fr::one();
+        assert_eq!(from_decimal::<Fr>(&to_decimal(&minus_one)), Some(minus_one));
+        assert_eq!(to_decimal(&Fr::zero()), "0");
+        assert_eq!(from_decimal::<Fr>("300"), Some(Fr::from(300)));
+        assert_eq!(from_decimal::<Fr>(modulus), None);
+        assert_eq!(from_decimal::<Fr>("-1"), None);
+    }
+}
@@ -0,0 +1,27 @@
+//! R1CS instances shared by the unit tests.
+
+use crate::matrix::R1CS;
+use halo2_proofs::halo2curves::bn256::Fr;
+
+/// `x^3 + x + 5 = out`, over `z = [1, out, x, x*x, x*x*x, x*x*x + x]`.
+pub(crate) fn cubic() -> R1CS<Fr> {
+    let one = Fr::one();
+    let mut r1cs = R1CS::new(1, 4);
+    r1cs.add_constraint(vec![(2, one)], vec![(2, one)], vec![(3, one)]);
+    r1cs.add_constraint(vec![(3, one)], vec![(2, one)], vec![(4, one)]);
+    r1cs.add_constraint(vec![(4, one), (2, one)], vec![(0, one)], vec![(5, one)]);
+    r1cs.add_constraint(vec![(5, one), (0, Fr::from(5))], vec![(0, one)], vec![(1, one)]);
+    r1cs
+}
+
+/// The witness of [`cubic`] for the given `x`.
+pub(crate) fn cubic_witness(x: u64) -> Vec<Fr> {
+    [1, x * x * x + x + 5, x, x * x, x * x * x, x * x * x + x].map(Fr::from).to_vec()
+}
+
+/// `x * x = y` with `y` public, and its witness for `x = 3`.
+pub(crate) fn square() -> (R1CS<Fr>, Vec<Fr>) {
+    let mut r1cs = R1CS::new(1, 1);
+    r1cs.add_constraint(vec![(2, Fr::one())], vec![(2, Fr::one())], vec![(1, Fr::one())]);
+    (r1cs, vec![Fr::one(), Fr::from(9), Fr::from(3)])
+}
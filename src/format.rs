@@ -0,0 +1,295 @@
+//! Versioned files for verifying keys and proofs.
+//!
+//! Both files start with a four byte magic and a `u32` version, and carry the
+//! [`digest`] of the R1CS they were produced for, so a proof is only checked
+//! against a verifying key of the same R1CS. The verifying key file also
+//! records the proving system, the field prime, `k` and the width of the
+//! [`R1CSConfig`](crate::R1CSConfig) layout. Integers are little-endian, and
+//! strings and byte strings are prefixed with their `u32` length.
+
+use std::io;
+use halo2_proofs::{arithmetic::FieldExt, plonk::VerifyingKey};
+
+use crate::{error::Error, field, matrix::R1CS, prover::{self, Backend}, r1cs::R1CSCircuit};
+
+/// Version written by this crate, and the only one it reads.
+pub const VERSION: u32 = 1;
+
+const VK_MAGIC: &[u8; 4] = b"r1vk";
+const PROOF_MAGIC: &[u8; 4] = b"r1pf";
+
+/// Hash of the shape and coefficients of `r1cs`, identifying the circuit a
+/// key or proof belongs to.
+pub fn digest<F: FieldExt>(r1cs: &R1CS<F>) -> [u8; 32] {
+    let mut state = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"r1cs-halo2-r1cs\0")
+        .to_state();
+    state.update(F::MODULUS.as_bytes());
+    for n in [r1cs.num_inputs, r1cs.num_witnesses, r1cs.num_constraints()] {
+        state.update(&(n as u64).to_le_bytes());
+    }
+    for lc in r1cs.constraints().flat_map(|(a, b, c)| [a, b, c]) {
+        state.update(&(lc.len() as u64).to_le_bytes());
+        for (wire, coeff) in lc {
+            state.update(&(*wire as u64).to_le_bytes());
+            state.update(coeff.to_repr().as_ref());
+        }
+    }
+    state.finalize().as_bytes().try_into().unwrap()
+}
+
+/// The circuit a verifying key was generated for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Commitment scheme and curve, as [`Backend::NAME`].
+    pub scheme: String,
+    /// Modulus of the scalar field, as `FieldExt::MODULUS`.
+    pub modulus: String,
+    pub k: u32,
+    /// Linear combination terms per row of the layout, the `WIDTH` of the
+    /// circuit.
+    pub width: u32,
+    /// [`digest`] of the R1CS.
+    pub digest: [u8; 32],
+}
+
+impl Header {
+    /// The header of keys generated by [`prover::keygen`] for `circuit`.
+    pub fn new<P: Backend, const WIDTH: usize>(params: &P, circuit: &R1CSCircuit<P::Scalar, WIDTH>) -> Self {
+        Header {
+            scheme: P::NAME.to_string(),
+            modulus: <P::Scalar as FieldExt>::MODULUS.to_string(),
+            k: params.k(),
+            width: WIDTH as u32,
+            digest: digest(circuit.r1cs()),
+        }
+    }
+
+    /// Checks that the key belongs to `r1cs`.
+    pub fn check_r1cs<F: FieldExt>(&self, r1cs: &R1CS<F>) -> Result<(), Error> {
+        if self.digest != digest(r1cs) {
+            return Err(Error::Mismatch("verifying key is for another R1CS".to_string()));
+        }
+        Ok(())
+    }
+
+    fn check<P: Backend, const WIDTH: usize>(&self, params: &P) -> Result<(), Error> {
+        if self.scheme != P::NAME || self.modulus != <P::Scalar as FieldExt>::MODULUS {
+            return Err(Error::Mismatch(format!("verifying key is for {} over {}", self.scheme, self.modulus)));
+        }
+        if self.width != WIDTH as u32 {
+            return Err(Error::Mismatch(format!("verifying key has width {}, expected {}", self.width, WIDTH)));
+        }
+        if self.k != params.k() {
+            return Err(Error::Mismatch(format!("verifying key has k = {}, parameters {}", self.k, params.k())));
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut impl io::Write) -> Result<(), Error> {
+        write_bytes(writer, self.scheme.as_bytes())?;
+        write_bytes(writer, self.modulus.as_bytes())?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.width.to_le_bytes())?;
+        writer.write_all(&self.digest)?;
+        Ok(())
+    }
+
+    fn read(reader: &mut impl io::Read) -> Result<Self, Error> {
+        let scheme = read_string(reader)?;
+        let modulus = read_string(reader)?;
+        let k = read_u32(reader)?;
+        let width = read_u32(reader)?;
+        let mut digest = [0; 32];
+        reader.read_exact(&mut digest)?;
+        Ok(Header { scheme, modulus, k, width, digest })
+    }
+}
+
+/// Writes `vk`, generated for `circuit`, with its [`Header`].
+pub fn write_vk<P: Backend, const WIDTH: usize>(
+    params: &P,
+    vk: &VerifyingKey<P::Curve>,
+    circuit: &R1CSCircuit<P::Scalar, WIDTH>,
+    writer: &mut impl io::Write,
+) -> Result<(), Error> {
+    writer.write_all(VK_MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    Header::new(params, circuit).write(writer)?;
+    prover::write_vk(vk, writer)
+}
+
+/// Reads a verifying key written by [`write_vk`] for a circuit of width
+/// `WIDTH`, rejecting keys for another proving system, width or parameter
+/// size.
+pub fn read_vk<P: Backend, const WIDTH: usize>(
+    params: &P,
+    reader: &mut impl io::Read,
+) -> Result<(Header, VerifyingKey<P::Curve>), Error> {
+    read_preamble(reader, VK_MAGIC)?;
+    let header = Header::read(reader)?;
+    header.check::<P, WIDTH>(params)?;
+    let vk = prover::read_vk::<P, WIDTH>(params, reader)?;
+    Ok((header, vk))
+}
+
+/// A proof with its public inputs and the [`digest`] of its R1CS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofFile<F> {
+    pub digest: [u8; 32],
+    pub public_inputs: Vec<F>,
+    pub proof: Vec<u8>,
+}
+
+impl<F: FieldExt> ProofFile<F> {
+    pub fn new(r1cs: &R1CS<F>, public_inputs: &[F], proof: Vec<u8>) -> Self {
+        ProofFile {
+            digest: digest(r1cs),
+            public_inputs: public_inputs.to_vec(),
+            proof,
+        }
+    }
+
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), Error> {
+        writer.write_all(PROOF_MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.digest)?;
+        writer.write_all(&(self.public_inputs.len() as u32).to_le_bytes())?;
+        for input in self.public_inputs.iter() {
+            write_bytes(writer, &field::to_le_bytes(input, field::repr_len::<F>()))?;
+        }
+        write_bytes(writer, &self.proof)
+    }
+
+    pub fn read(reader: &mut impl io::Read) -> Result<Self, Error> {
+        read_preamble(reader, PROOF_MAGIC)?;
+        let mut digest = [0; 32];
+        reader.read_exact(&mut digest)?;
+        let n = read_u32(reader)?;
+        let public_inputs = (0..n)
+            .map(|_| {
+                let bytes = read_bytes(reader)?;
+                field::from_le_bytes(&bytes).ok_or_else(|| Error::OutOfRange {
+                    value: field::le_bytes_to_hex(&bytes),
+                    modulus: field::modulus_hex::<F>(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let proof = read_bytes(reader)?;
+        Ok(ProofFile { digest, public_inputs, proof })
+    }
+
+    /// Verifies the proof against a key read by [`read_vk`], rejecting proofs
+    /// of another R1CS.
+    pub fn verify<P: Backend<Scalar = F>>(
+        &self,
+        params: &P,
+        header: &Header,
+        vk: &VerifyingKey<P::Curve>,
+    ) -> Result<(), Error> {
+        if self.digest != header.digest {
+            return Err(Error::Mismatch("proof is for another R1CS than the verifying key".to_string()));
+        }
+        prover::verify(params, vk, &self.public_inputs, &self.proof)
+    }
+}
+
+fn read_preamble(reader: &mut impl io::Read, magic: &[u8; 4]) -> Result<(), Error> {
+    let mut found = [0; 4];
+    reader.read_exact(&mut found)?;
+    if &found != magic {
+        return Err(Error::Format(format!("expected magic {:?}", String::from_utf8_lossy(magic))));
+    }
+    let version = read_u32(reader)?;
+    if version != VERSION {
+        return Err(Error::Unsupported(format!("file version {}", version)));
+    }
+    Ok(())
+}
+
+fn write_bytes(writer: &mut impl io::Write, bytes: &[u8]) -> Result<(), Error> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl io::Read) -> Result<u32, Error> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_bytes(reader: &mut impl io::Read) -> Result<Vec<u8>, Error> {
+    let len = read_u32(reader)? as usize;
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::Format("unexpected end of input".to_string()));
+    }
+    Ok(bytes)
+}
+
+fn read_string(reader: &mut impl io::Read) -> Result<String, Error> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| Error::Format("string is not utf-8".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::square;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_digest() {
+        let (r1cs, _) = square();
+        let mut other = r1cs.clone();
+        other.c[0][0].1 = Fr::from(2);
+        assert_eq!(digest(&r1cs), digest(&r1cs.clone()));
+        assert_ne!(digest(&r1cs), digest(&other));
+    }
+
+    #[test]
+    fn test_vk_and_proof_files() {
+        let (r1cs, z) = square();
+        let circuit = R1CSCircuit::<Fr>::new(r1cs.clone(), z.clone()).unwrap();
+        let params = prover::setup(6);
+        let pk = prover::keygen(&params, &circuit).unwrap();
+        let proof = prover::prove(&params, &pk, circuit.clone()).unwrap();
+
+        let mut bytes = vec![];
+        write_vk(&params, pk.get_vk(), &circuit, &mut bytes).unwrap();
+        let (header, vk) = read_vk::<_, 1>(&params, &mut &bytes[..]).unwrap();
+        assert_eq!((header.k, header.width), (6, 1));
+        assert!(header.check_r1cs(&r1cs).is_ok());
+        assert!(matches!(read_vk::<_, 1>(&prover::setup(7), &mut &bytes[..]), Err(Error::Mismatch(_))));
+        assert!(matches!(read_vk::<_, 4>(&params, &mut &bytes[..]), Err(Error::Mismatch(_))));
+
+        let mut bytes = vec![];
+        ProofFile::new(&r1cs, r1cs.public_inputs(&z).unwrap(), proof).write(&mut bytes).unwrap();
+        let file = ProofFile::read(&mut &bytes[..]).unwrap();
+        assert_eq!(file.public_inputs, vec![Fr::from(9)]);
+        assert!(file.verify(&params, &header, &vk).is_ok());
+
+        // A proof claiming another R1CS is rejected before verification.
+        let mut other = r1cs.clone();
+        other.add_constraint(vec![], vec![], vec![]);
+        let forged = ProofFile { digest: digest(&other), ..file };
+        assert!(matches!(forged.verify(&params, &header, &vk), Err(Error::Mismatch(_))));
+        assert!(matches!(header.check_r1cs(&other), Err(Error::Mismatch(_))));
+    }
+
+    #[test]
+    fn test_vk_width() {
+        let (r1cs, z) = square();
+        let circuit = R1CSCircuit::<Fr, 4>::new(r1cs, z).unwrap();
+        let params = prover::setup(6);
+        let pk = prover::keygen(&params, &circuit).unwrap();
+
+        let mut bytes = vec![];
+        write_vk(&params, pk.get_vk(), &circuit, &mut bytes).unwrap();
+        let (header, _) = read_vk::<_, 4>(&params, &mut &bytes[..]).unwrap();
+        assert_eq!(header.width, 4);
+        assert!(matches!(read_vk::<_, 1>(&params, &mut &bytes[..]), Err(Error::Mismatch(_))));
+    }
+}
@@ -0,0 +1,92 @@
+//! DOT export of an [`R1CS`] as a wire graph.
+//!
+//! halo2's `circuit_dot_graph` only shows the namespaces of the layouter;
+//! this shows the constraint structure instead. Each wire of `z` is a node,
+//! and each constraint `(A·z) * (B·z) = C·z` adds an edge from every wire of
+//! A and B to every wire of C, labelled with the constraint index. The one
+//! wire is drawn but not connected, as nearly every constraint uses it.
+
+use std::collections::BTreeSet;
+use halo2_proofs::arithmetic::FieldExt;
+use tabbycat::{attributes::label, AttrList, Edge, GraphBuilder, GraphType, Identity, StmtList};
+
+use crate::matrix::R1CS;
+
+/// Builds a DOT graph of `r1cs`, naming wires by their position in `z`.
+pub fn dot_graph<F: FieldExt>(r1cs: &R1CS<F>) -> String {
+    dot_graph_with_names(r1cs, |wire| default_name(r1cs.num_inputs, wire))
+}
+
+/// Builds a DOT graph of `r1cs`, naming each wire with `name`.
+pub fn dot_graph_with_names<F: FieldExt>(r1cs: &R1CS<F>, name: impl Fn(usize) -> String) -> String {
+    // tabbycat borrows labels, so they must outlive the statement list.
+    let node_labels: Vec<String> = (0..r1cs.num_variables()).map(name).collect();
+    let edge_labels: Vec<String> = (0..r1cs.num_constraints()).map(|i| format!("c{}", i)).collect();
+
+    let mut stmts = StmtList::new();
+    for (wire, node_label) in node_labels.iter().enumerate() {
+        stmts = stmts.add_node(
+            wire.into(),
+            None,
+            Some(AttrList::new().add_pair(label(node_label))),
+        );
+    }
+
+    for (i, (a, b, c)) in r1cs.constraints().enumerate() {
+        let wires = |lc: &[(usize, F)]| -> BTreeSet<usize> {
+            lc.iter().map(|(wire, _)| *wire).filter(|wire| *wire != 0).collect()
+        };
+        let inputs: BTreeSet<usize> = wires(a).union(&wires(b)).copied().collect();
+        let outputs = wires(c);
+        let (inputs, outputs) = if outputs.is_empty() {
+            (wires(a), wires(b))
+        } else {
+            (inputs, outputs)
+        };
+
+        for from in inputs.iter() {
+            for to in outputs.iter() {
+                stmts = stmts.add_edge(
+                    Edge::head_node((*from).into(), None)
+                        .arrow_to_node((*to).into(), None)
+                        .add_attrpair(label(&edge_labels[i])),
+                );
+            }
+        }
+    }
+
+    GraphBuilder::default()
+        .graph_type(GraphType::DiGraph)
+        .strict(false)
+        .id(Identity::id("r1cs").unwrap())
+        .stmts(stmts)
+        .build()
+        .unwrap()
+        .to_string()
+}
+
+pub(crate) fn default_name(num_inputs: usize, wire: usize) -> String {
+    if wire == 0 {
+        "one".to_string()
+    } else if wire <= num_inputs {
+        format!("public z[{}]", wire)
+    } else {
+        format!("z[{}]", wire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dot_graph;
+    use crate::fixtures::square;
+
+    #[test]
+    fn test_dot_graph() {
+        let (r1cs, _) = square();
+
+        let dot = dot_graph(&r1cs);
+        assert!(dot.starts_with("digraph r1cs"));
+        assert!(dot.contains("public z[1]"));
+        assert!(dot.contains("c0"));
+    }
+}
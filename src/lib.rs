@@ -1 +1,45 @@
-mod r1cs;
\ No newline at end of file
+//! Proving R1CS instances with halo2.
+//!
+//! An [`R1CS`] instance and its witness vector, loaded from circom or written
+//! with [`R1CSBuilder`], are compiled by [`R1CSChip`] into PLONKish columns,
+//! and [`R1CSCircuit`] wraps both as a halo2 [`Circuit`]. The [`prover`]
+//! module generates keys, proofs and verifies them with KZG over BN254 or IPA
+//! over the Pasta curves, [`format`] stores keys and proofs tied to their
+//! R1CS, and [`debug`] explains `MockProver` failures in terms of the R1CS.
+//! With the `evm` feature, KZG proofs can also be verified on-chain by a
+//! generated Yul contract. Witnesses come from circom, or from [`solver`] for
+//! circuits simple enough to be solved natively. With the `bellman` and
+//! `arkworks` features, existing bellman gadgets and arkworks constraint
+//! systems can be imported into an [`R1CS`] as well.
+//!
+//! [`Circuit`]: halo2_proofs::plonk::Circuit
+
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
+#[cfg(feature = "bellman")]
+pub mod bellman;
+pub mod builder;
+pub mod circom;
+pub mod debug;
+pub mod error;
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod field;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod format;
+pub mod graph;
+pub mod matrix;
+pub mod optimize;
+pub mod prover;
+pub mod r1cs;
+pub mod solver;
+
+pub use builder::{R1CSBuilder, ONE};
+pub use error::Error;
+pub use matrix::{LinearCombination, SparseMatrix, Unsatisfied, R1CS};
+pub use r1cs::{R1CSChip, R1CSCircuit, R1CSComposer, R1CSConfig, RowBudget};
+
+pub use halo2_proofs;
+pub use halo2_proofs::arithmetic::FieldExt;
+pub use halo2_proofs::halo2curves::{bn256, pasta};
@@ -0,0 +1,181 @@
+use std::fmt;
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Sparse linear combination over the witness vector `z`, as `(col, coeff)` entries.
+pub type LinearCombination<F> = Vec<(usize, F)>;
+
+/// Row-major sparse matrix, one linear combination per row.
+pub type SparseMatrix<F> = Vec<LinearCombination<F>>;
+
+/// An R1CS instance `(A·z) ∘ (B·z) = C·z`.
+///
+/// The witness vector is laid out as `z = [1, public inputs.., private witnesses..]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct R1CS<F: FieldExt> {
+    pub a: SparseMatrix<F>,
+    pub b: SparseMatrix<F>,
+    pub c: SparseMatrix<F>,
+    /// Number of public inputs, not counting the constant one wire.
+    pub num_inputs: usize,
+    /// Number of private witnesses.
+    pub num_witnesses: usize,
+    /// Little-endian prime the instance was compiled for, if its source
+    /// declares one, as circom files do.
+    pub prime: Option<Vec<u8>>,
+}
+
+/// Reason a witness vector does not satisfy an [`R1CS`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsatisfied {
+    /// `z` does not have `1 + num_inputs + num_witnesses` entries.
+    WitnessLength { expected: usize, actual: usize },
+    /// `z[0]` is not one.
+    ConstantWire,
+    /// Row `row` refers to column `col`, which is outside `z`.
+    UnknownVariable { row: usize, col: usize },
+    /// Row `row` is the first constraint with `(A·z) * (B·z) != C·z`.
+    Constraint(usize),
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WitnessLength { expected, actual } => {
+                write!(f, "witness has {} entries, expected {}", actual, expected)
+            }
+            Self::ConstantWire => write!(f, "z[0] must be one"),
+            Self::UnknownVariable { row, col } => {
+                write!(f, "constraint {} refers to unknown variable {}", row, col)
+            }
+            Self::Constraint(row) => write!(f, "constraint {} is not satisfied", row),
+        }
+    }
+}
+
+impl std::error::Error for Unsatisfied {}
+
+/// Evaluates `lc` at `z`, or returns the first out-of-range column.
+pub fn evaluate<F: FieldExt>(lc: &[(usize, F)], z: &[F]) -> Result<F, usize> {
+    lc.iter().try_fold(F::zero(), |acc, &(col, coeff)| {
+        z.get(col).map(|v| acc + coeff * v).ok_or(col)
+    })
+}
+
+impl<F: FieldExt> R1CS<F> {
+    pub fn new(num_inputs: usize, num_witnesses: usize) -> Self {
+        R1CS {
+            a: vec![],
+            b: vec![],
+            c: vec![],
+            num_inputs,
+            num_witnesses,
+            prime: None,
+        }
+    }
+
+    /// Checks that the declared prime, if any, is the modulus of `F`.
+    pub fn check_field(&self) -> Result<(), crate::Error> {
+        match &self.prime {
+            Some(prime) => crate::field::check_modulus::<F>(prime),
+            None => Ok(()),
+        }
+    }
+
+    /// Length of `z`, including the constant one wire.
+    pub fn num_variables(&self) -> usize {
+        1 + self.num_inputs + self.num_witnesses
+    }
+
+    /// The public inputs `z[1..=num_inputs]`, as laid out in the instance
+    /// column, or `None` if `z` is too short to hold them.
+    pub fn public_inputs<'a>(&self, z: &'a [F]) -> Option<&'a [F]> {
+        z.get(1..1 + self.num_inputs)
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn add_constraint(
+        &mut self,
+        a: LinearCombination<F>,
+        b: LinearCombination<F>,
+        c: LinearCombination<F>,
+    ) {
+        self.a.push(a);
+        self.b.push(b);
+        self.c.push(c);
+    }
+
+    /// Returns row `i` of A, B and C.
+    pub fn constraint(&self, i: usize) -> (&[(usize, F)], &[(usize, F)], &[(usize, F)]) {
+        (&self.a[i], &self.b[i], &self.c[i])
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = (&[(usize, F)], &[(usize, F)], &[(usize, F)])> + '_ {
+        (0..self.num_constraints()).map(move |i| self.constraint(i))
+    }
+
+    /// Checks `(A·z) ∘ (B·z) = C·z`, reporting the first failing row.
+    pub fn is_satisfied(&self, z: &[F]) -> Result<(), Unsatisfied> {
+        if z.len() != self.num_variables() {
+            return Err(Unsatisfied::WitnessLength {
+                expected: self.num_variables(),
+                actual: z.len(),
+            });
+        }
+        if z[0] != F::one() {
+            return Err(Unsatisfied::ConstantWire);
+        }
+
+        for (row, (a, b, c)) in self.constraints().enumerate() {
+            let eval = |lc| evaluate(lc, z).map_err(|col| Unsatisfied::UnknownVariable { row, col });
+            if eval(a)? * eval(b)? != eval(c)? {
+                return Err(Unsatisfied::Constraint(row));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Unsatisfied;
+    use crate::fixtures::square;
+    use halo2_proofs::halo2curves::bn256::Fr as Fp;
+
+    #[test]
+    fn test_is_satisfied() {
+        let (r1cs, z) = square();
+        assert_eq!(r1cs.is_satisfied(&z), Ok(()));
+
+        let mut wrong = z.clone();
+        wrong[1] = Fp::from(10);
+        assert_eq!(r1cs.is_satisfied(&wrong), Err(Unsatisfied::Constraint(0)));
+        assert_eq!(
+            r1cs.is_satisfied(&z[..2]),
+            Err(Unsatisfied::WitnessLength { expected: 3, actual: 2 })
+        );
+
+        let mut zero = z.clone();
+        zero[0] = Fp::zero();
+        assert_eq!(r1cs.is_satisfied(&zero), Err(Unsatisfied::ConstantWire));
+    }
+
+    #[test]
+    fn test_public_inputs() {
+        let (r1cs, z) = square();
+
+        assert_eq!(r1cs.public_inputs(&z), Some(&z[1..2]));
+        assert_eq!(r1cs.public_inputs(&z[..1]), None);
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        let (mut r1cs, z) = square();
+        r1cs.add_constraint(vec![(3, Fp::one())], vec![], vec![]);
+
+        assert_eq!(r1cs.is_satisfied(&z), Err(Unsatisfied::UnknownVariable { row: 1, col: 3 }));
+    }
+}
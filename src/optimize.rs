@@ -0,0 +1,239 @@
+//! Optimization passes over an [`R1CS`] before it is compiled to halo2.
+
+use std::collections::{BTreeMap, BTreeSet};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{matrix::{LinearCombination, R1CS}, r1cs::R1CSChip};
+
+/// Outcome of [`eliminate_linear`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report<F> {
+    pub constraints_before: usize,
+    pub constraints_after: usize,
+    /// Constraint rows of the layout of width `WIDTH` before and after the
+    /// pass.
+    pub rows_before: usize,
+    pub rows_after: usize,
+    /// Each eliminated private witness with the linear combination it was
+    /// replaced by, in elimination order.
+    pub substitutions: Vec<(usize, LinearCombination<F>)>,
+}
+
+impl<F> Report<F> {
+    pub fn rows_saved(&self) -> usize {
+        self.rows_before.saturating_sub(self.rows_after)
+    }
+}
+
+/// Removes linear constraints, those where A·z or B·z is a constant, by
+/// solving each for one of its private witnesses and substituting it into
+/// the remaining constraints, like circom's `--O2`.
+///
+/// The witness layout is unchanged: eliminated witnesses keep their index in
+/// `z` but no longer appear in any constraint, so a witness that satisfies
+/// `r1cs` also satisfies the result. Linear constraints over only the one
+/// wire and public inputs are kept, as they constrain the statement itself.
+/// The rows of the [`Report`] are counted for an [`R1CSChip`] of width
+/// `WIDTH`.
+pub fn eliminate_linear<F: FieldExt, const WIDTH: usize>(r1cs: &R1CS<F>) -> (R1CS<F>, Report<F>) {
+    let mut constraints: Vec<Option<[BTreeMap<usize, F>; 3]>> = r1cs
+        .constraints()
+        .map(|(a, b, c)| Some([normalize(a), normalize(b), normalize(c)]))
+        .collect();
+
+    let mut occurrences: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        for lc in constraint.iter().flatten() {
+            for col in lc.keys() {
+                occurrences.entry(*col).or_default().insert(i);
+            }
+        }
+    }
+
+    let is_private = |col: usize| col > r1cs.num_inputs;
+    let mut substitutions = vec![];
+    let mut worklist: BTreeSet<usize> = (0..constraints.len()).collect();
+
+    while let Some(i) = worklist.pop_first() {
+        let linear = match &constraints[i] {
+            Some(constraint) => match as_linear(constraint) {
+                Some(linear) => linear,
+                None => continue,
+            },
+            None => continue,
+        };
+
+        if linear.is_empty() {
+            // 0 = 0
+            remove(&mut constraints, &mut occurrences, i);
+            continue;
+        }
+
+        let pivot = linear
+            .keys()
+            .copied()
+            .filter(|col| is_private(*col))
+            .min_by_key(|col| occurrences.get(col).map_or(0, |rows| rows.len()));
+        let pivot = match pivot {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+
+        // pivot = -(linear - coeff * pivot) / coeff
+        let scale = -linear[&pivot].invert().unwrap();
+        let expr: BTreeMap<usize, F> = linear
+            .iter()
+            .filter(|(col, _)| **col != pivot)
+            .map(|(col, coeff)| (*col, *coeff * scale))
+            .collect();
+
+        remove(&mut constraints, &mut occurrences, i);
+        let rows = occurrences.remove(&pivot).unwrap_or_default();
+        for row in rows {
+            // Occurrences may be stale after cancellation; skip removed rows.
+            let constraint = match constraints[row].as_mut() {
+                Some(constraint) => constraint,
+                None => continue,
+            };
+            for lc in constraint.iter_mut() {
+                if let Some(d) = lc.remove(&pivot) {
+                    for (col, coeff) in expr.iter() {
+                        add_term(lc, *col, d * coeff);
+                    }
+                }
+            }
+            for col in constraint.iter().flat_map(|lc| lc.keys()) {
+                occurrences.entry(*col).or_default().insert(row);
+            }
+            worklist.insert(row);
+        }
+
+        substitutions.push((pivot, expr.into_iter().collect()));
+    }
+
+    let mut out = R1CS::new(r1cs.num_inputs, r1cs.num_witnesses);
+    out.prime = r1cs.prime.clone();
+    for [a, b, c] in constraints.into_iter().flatten() {
+        out.add_constraint(a.into_iter().collect(), b.into_iter().collect(), c.into_iter().collect());
+    }
+
+    let report = Report {
+        constraints_before: r1cs.num_constraints(),
+        constraints_after: out.num_constraints(),
+        rows_before: rows::<F, WIDTH>(r1cs),
+        rows_after: rows::<F, WIDTH>(&out),
+        substitutions,
+    };
+    (out, report)
+}
+
+/// Merges repeated columns and drops zero coefficients.
+fn normalize<F: FieldExt>(lc: &[(usize, F)]) -> BTreeMap<usize, F> {
+    let mut out = BTreeMap::new();
+    for (col, coeff) in lc {
+        add_term(&mut out, *col, *coeff);
+    }
+    out
+}
+
+fn add_term<F: FieldExt>(lc: &mut BTreeMap<usize, F>, col: usize, coeff: F) {
+    let sum = lc.get(&col).copied().unwrap_or_else(F::zero) + coeff;
+    if sum == F::zero() {
+        lc.remove(&col);
+    } else {
+        lc.insert(col, sum);
+    }
+}
+
+/// If A·z or B·z is a constant `k`, returns `L` with `L·z = 0` equivalent to
+/// the constraint, i.e. `k * (B·z) - C·z` or `k * (A·z) - C·z`.
+fn as_linear<F: FieldExt>([a, b, c]: &[BTreeMap<usize, F>; 3]) -> Option<BTreeMap<usize, F>> {
+    let constant = |lc: &BTreeMap<usize, F>| match lc.len() {
+        0 => Some(F::zero()),
+        1 => lc.get(&0).copied(),
+        _ => None,
+    };
+    let (k, other) = match (constant(a), constant(b)) {
+        (Some(k), _) => (k, b),
+        (_, Some(k)) => (k, a),
+        _ => return None,
+    };
+
+    let mut linear = BTreeMap::new();
+    for (col, coeff) in other {
+        add_term(&mut linear, *col, k * coeff);
+    }
+    for (col, coeff) in c {
+        add_term(&mut linear, *col, -*coeff);
+    }
+    Some(linear)
+}
+
+fn remove<F>(
+    constraints: &mut [Option<[BTreeMap<usize, F>; 3]>],
+    occurrences: &mut BTreeMap<usize, BTreeSet<usize>>,
+    i: usize,
+) {
+    if let Some(constraint) = constraints[i].take() {
+        for col in constraint.iter().flat_map(|lc| lc.keys()) {
+            if let Some(rows) = occurrences.get_mut(col) {
+                rows.remove(&i);
+            }
+        }
+    }
+}
+
+fn rows<F: FieldExt, const WIDTH: usize>(r1cs: &R1CS<F>) -> usize {
+    r1cs.constraints()
+        .map(|(a, b, c)| R1CSChip::<F, WIDTH>::constraint_rows(a.len(), b.len(), c.len()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::eliminate_linear;
+    use crate::fixtures::{cubic, cubic_witness};
+    use crate::{R1CSCircuit, R1CS};
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr as Fp};
+
+    #[test]
+    fn test_eliminate_linear() {
+        let (r1cs, z) = (cubic(), cubic_witness(3));
+        let (optimized, report) = eliminate_linear::<_, 1>(&r1cs);
+
+        assert_eq!(report.constraints_before, 4);
+        assert_eq!(report.constraints_after, 2);
+        assert_eq!(report.substitutions.len(), 2);
+        assert!(report.rows_saved() > 0);
+        assert_eq!(optimized.is_satisfied(&z), Ok(()));
+
+        let mut bad = z.clone();
+        bad[1] = Fp::from(36);
+        assert!(optimized.is_satisfied(&bad).is_err());
+
+        let circuit = R1CSCircuit::<Fp>::new(optimized, z).unwrap();
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![vec![Fp::from(35)]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_keep_public_linear() {
+        // out * 1 = 5 only involves a public input.
+        let mut r1cs = R1CS::<Fp>::new(1, 0);
+        r1cs.add_constraint(vec![(1, Fp::one())], vec![(0, Fp::one())], vec![(0, Fp::from(5))]);
+        let (optimized, report) = eliminate_linear::<_, 1>(&r1cs);
+
+        assert_eq!(optimized, r1cs);
+        assert!(report.substitutions.is_empty());
+    }
+
+    #[test]
+    fn test_rows_at_width() {
+        let r1cs = cubic();
+        let (optimized, report) = eliminate_linear::<_, 4>(&r1cs);
+
+        let before = R1CSCircuit::<Fp, 4>::without_witness(r1cs).unwrap().row_budget();
+        let after = R1CSCircuit::<Fp, 4>::without_witness(optimized).unwrap().row_budget();
+        assert_eq!((report.rows_before, report.rows_after), (before.constraints, after.constraints));
+    }
+}
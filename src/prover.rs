@@ -0,0 +1,449 @@
+//! Key generation, proving and verification of [`R1CSCircuit`]s.
+//!
+//! The pipeline is generic over the commitment scheme through [`Backend`],
+//! implemented by the parameters of each scheme: KZG over BN254, which needs
+//! a trusted setup, and IPA over the Pasta cycle, which does not. Circuits
+//! over `pasta::Fp` commit on Vesta and circuits over `pasta::Fq` on Pallas.
+//! Keys and proofs are made for an [`R1CSCircuit`] of any layout width.
+
+use std::io;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        pasta::{EpAffine, EqAffine},
+    },
+    plonk::{self, create_proof, keygen_pk, keygen_vk, verify_proof, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as IPASingleStrategy,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use rand_core::OsRng;
+
+use crate::{error::Error, r1cs::R1CSCircuit};
+
+/// Commitment parameters of a proving backend, and the halo2 calls that
+/// depend on its commitment scheme.
+pub trait Backend: Sized {
+    /// Identifies the scheme and curve in key files, e.g. `"kzg-bn256"`.
+    const NAME: &'static str;
+    /// Field of the R1CS.
+    type Scalar: FieldExt;
+    /// Curve commitments are made on.
+    type Curve: CurveAffine<ScalarExt = Self::Scalar>;
+
+    /// Generates fresh parameters for circuits of up to `2^k` rows.
+    fn generate(k: u32) -> Self;
+    fn k(&self) -> u32;
+    fn write(&self, writer: &mut impl io::Write) -> Result<(), Error>;
+    fn read(reader: &mut impl io::Read) -> Result<Self, Error>;
+
+    fn keygen_vk<const WIDTH: usize>(
+        &self,
+        circuit: &R1CSCircuit<Self::Scalar, WIDTH>,
+    ) -> Result<VerifyingKey<Self::Curve>, plonk::Error>;
+    fn keygen_pk<const WIDTH: usize>(
+        &self,
+        vk: VerifyingKey<Self::Curve>,
+        circuit: &R1CSCircuit<Self::Scalar, WIDTH>,
+    ) -> Result<ProvingKey<Self::Curve>, plonk::Error>;
+    fn read_vk<const WIDTH: usize>(&self, reader: &mut impl io::Read) -> Result<VerifyingKey<Self::Curve>, Error>;
+    fn create_proof<const WIDTH: usize>(
+        &self,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: R1CSCircuit<Self::Scalar, WIDTH>,
+        public_inputs: &[Self::Scalar],
+    ) -> Result<Vec<u8>, plonk::Error>;
+    fn verify_proof(
+        &self,
+        vk: &VerifyingKey<Self::Curve>,
+        public_inputs: &[Self::Scalar],
+        proof: &[u8],
+    ) -> Result<(), plonk::Error>;
+}
+
+impl Backend for ParamsKZG<Bn256> {
+    const NAME: &'static str = "kzg-bn256";
+    type Scalar = Fr;
+    type Curve = G1Affine;
+
+    fn generate(k: u32) -> Self {
+        ParamsKZG::setup(k, OsRng)
+    }
+
+    fn k(&self) -> u32 {
+        Params::k(self)
+    }
+
+    fn write(&self, writer: &mut impl io::Write) -> Result<(), Error> {
+        Ok(Params::write(self, writer)?)
+    }
+
+    fn read(reader: &mut impl io::Read) -> Result<Self, Error> {
+        Ok(<Self as Params<G1Affine>>::read(reader)?)
+    }
+
+    fn keygen_vk<const WIDTH: usize>(
+        &self,
+        circuit: &R1CSCircuit<Fr, WIDTH>,
+    ) -> Result<VerifyingKey<G1Affine>, plonk::Error> {
+        keygen_vk(self, circuit)
+    }
+
+    fn keygen_pk<const WIDTH: usize>(
+        &self,
+        vk: VerifyingKey<G1Affine>,
+        circuit: &R1CSCircuit<Fr, WIDTH>,
+    ) -> Result<ProvingKey<G1Affine>, plonk::Error> {
+        keygen_pk(self, vk, circuit)
+    }
+
+    fn read_vk<const WIDTH: usize>(&self, reader: &mut impl io::Read) -> Result<VerifyingKey<G1Affine>, Error> {
+        Ok(VerifyingKey::read::<_, R1CSCircuit<Fr, WIDTH>>(reader, SerdeFormat::Processed)?)
+    }
+
+    fn create_proof<const WIDTH: usize>(
+        &self,
+        pk: &ProvingKey<G1Affine>,
+        circuit: R1CSCircuit<Fr, WIDTH>,
+        public_inputs: &[Fr],
+    ) -> Result<Vec<u8>, plonk::Error> {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            self,
+            pk,
+            &[circuit],
+            &[&[public_inputs]],
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        &self,
+        vk: &VerifyingKey<G1Affine>,
+        public_inputs: &[Fr],
+        proof: &[u8],
+    ) -> Result<(), plonk::Error> {
+        let strategy = SingleStrategy::new(self);
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+            self.verifier_params(),
+            vk,
+            strategy,
+            &[&[public_inputs]],
+            &mut transcript,
+        )?;
+        Ok(())
+    }
+}
+
+macro_rules! ipa_backend {
+    ($curve:ty, $name:expr) => {
+        impl Backend for ParamsIPA<$curve> {
+            const NAME: &'static str = $name;
+            type Scalar = <$curve as CurveAffine>::ScalarExt;
+            type Curve = $curve;
+
+            fn generate(k: u32) -> Self {
+                ParamsIPA::new(k)
+            }
+
+            fn k(&self) -> u32 {
+                Params::k(self)
+            }
+
+            fn write(&self, writer: &mut impl io::Write) -> Result<(), Error> {
+                Ok(Params::write(self, writer)?)
+            }
+
+            fn read(reader: &mut impl io::Read) -> Result<Self, Error> {
+                Ok(<Self as Params<$curve>>::read(reader)?)
+            }
+
+            fn keygen_vk<const WIDTH: usize>(
+                &self,
+                circuit: &R1CSCircuit<Self::Scalar, WIDTH>,
+            ) -> Result<VerifyingKey<$curve>, plonk::Error> {
+                keygen_vk(self, circuit)
+            }
+
+            fn keygen_pk<const WIDTH: usize>(
+                &self,
+                vk: VerifyingKey<$curve>,
+                circuit: &R1CSCircuit<Self::Scalar, WIDTH>,
+            ) -> Result<ProvingKey<$curve>, plonk::Error> {
+                keygen_pk(self, vk, circuit)
+            }
+
+            fn read_vk<const WIDTH: usize>(&self, reader: &mut impl io::Read) -> Result<VerifyingKey<$curve>, Error> {
+                Ok(VerifyingKey::read::<_, R1CSCircuit<Self::Scalar, WIDTH>>(reader, SerdeFormat::Processed)?)
+            }
+
+            fn create_proof<const WIDTH: usize>(
+                &self,
+                pk: &ProvingKey<$curve>,
+                circuit: R1CSCircuit<Self::Scalar, WIDTH>,
+                public_inputs: &[Self::Scalar],
+            ) -> Result<Vec<u8>, plonk::Error> {
+                let mut transcript = Blake2bWrite::<_, $curve, Challenge255<_>>::init(vec![]);
+                create_proof::<IPACommitmentScheme<$curve>, ProverIPA<'_, $curve>, _, _, _, _>(
+                    self,
+                    pk,
+                    &[circuit],
+                    &[&[public_inputs]],
+                    OsRng,
+                    &mut transcript,
+                )?;
+                Ok(transcript.finalize())
+            }
+
+            fn verify_proof(
+                &self,
+                vk: &VerifyingKey<$curve>,
+                public_inputs: &[Self::Scalar],
+                proof: &[u8],
+            ) -> Result<(), plonk::Error> {
+                let strategy = IPASingleStrategy::new(self);
+                let mut transcript = Blake2bRead::<_, $curve, Challenge255<_>>::init(proof);
+                verify_proof::<IPACommitmentScheme<$curve>, VerifierIPA<'_, $curve>, _, _, _>(
+                    self.verifier_params(),
+                    vk,
+                    strategy,
+                    &[&[public_inputs]],
+                    &mut transcript,
+                )?;
+                Ok(())
+            }
+        }
+    };
+}
+
+// Vesta's scalar field is `pasta::Fp`, Pallas' is `pasta::Fq`.
+ipa_backend!(EqAffine, "ipa-vesta");
+ipa_backend!(EpAffine, "ipa-pallas");
+
+/// Generates fresh KZG parameters for circuits of up to `2^k` rows.
+///
+/// The toxic waste is sampled from the OS and discarded, which is fine for
+/// testing; production deployments should load parameters from a ceremony
+/// with [`read_params`].
+pub fn setup(k: u32) -> ParamsKZG<Bn256> {
+    ParamsKZG::generate(k)
+}
+
+/// Generates KZG parameters of the smallest size `circuit` fits in.
+pub fn setup_for<const WIDTH: usize>(circuit: &R1CSCircuit<Fr, WIDTH>) -> ParamsKZG<Bn256> {
+    setup_for_backend(circuit)
+}
+
+/// Generates parameters of any backend of the smallest size `circuit` fits in.
+pub fn setup_for_backend<P: Backend, const WIDTH: usize>(circuit: &R1CSCircuit<P::Scalar, WIDTH>) -> P {
+    P::generate(circuit.min_k())
+}
+
+/// Generates the proving key, which embeds the verifying key, for the shape
+/// of `circuit`; its witness, if any, is ignored.
+///
+/// Fails with [`Error::NotEnoughRows`] if `params` are too small for `circuit`.
+pub fn keygen<P: Backend, const WIDTH: usize>(
+    params: &P,
+    circuit: &R1CSCircuit<P::Scalar, WIDTH>,
+) -> Result<ProvingKey<P::Curve>, Error> {
+    circuit.check_k(params.k())?;
+    let vk = params.keygen_vk(circuit)?;
+    keygen_pk_from_vk(params, vk, circuit)
+}
+
+/// Rebuilds the proving key for `circuit` from a deserialized verifying key.
+pub fn keygen_pk_from_vk<P: Backend, const WIDTH: usize>(
+    params: &P,
+    vk: VerifyingKey<P::Curve>,
+    circuit: &R1CSCircuit<P::Scalar, WIDTH>,
+) -> Result<ProvingKey<P::Curve>, Error> {
+    Ok(params.keygen_pk(vk, circuit)?)
+}
+
+/// Proves that the witness of `circuit` satisfies its R1CS, returning the
+/// proof bytes.
+///
+/// The witness is checked natively first, so an unsatisfied constraint is
+/// reported by index rather than as an opaque proving failure.
+pub fn prove<P: Backend, const WIDTH: usize>(
+    params: &P,
+    pk: &ProvingKey<P::Curve>,
+    circuit: R1CSCircuit<P::Scalar, WIDTH>,
+) -> Result<Vec<u8>, Error> {
+    let z = circuit
+        .known_witness()
+        .ok_or_else(|| Error::Unsupported("proving a circuit without a witness".to_string()))?;
+    circuit.r1cs().is_satisfied(&z)?;
+    let public_inputs = circuit.r1cs().public_inputs(&z).expect("z has every variable").to_vec();
+    Ok(params.create_proof(pk, circuit, &public_inputs)?)
+}
+
+/// Verifies `proof` against `vk` and the public inputs `z[1..=num_inputs]`.
+pub fn verify<P: Backend>(
+    params: &P,
+    vk: &VerifyingKey<P::Curve>,
+    public_inputs: &[P::Scalar],
+    proof: &[u8],
+) -> Result<(), Error> {
+    Ok(params.verify_proof(vk, public_inputs, proof)?)
+}
+
+pub fn write_params<P: Backend>(params: &P, writer: &mut impl io::Write) -> Result<(), Error> {
+    params.write(writer)
+}
+
+/// Reads KZG parameters; use [`Backend::read`] for other backends.
+pub fn read_params(reader: &mut impl io::Read) -> Result<ParamsKZG<Bn256>, Error> {
+    <ParamsKZG<Bn256> as Backend>::read(reader)
+}
+
+pub fn write_vk<C: CurveAffine>(vk: &VerifyingKey<C>, writer: &mut impl io::Write) -> Result<(), Error> {
+    Ok(vk.write(writer, SerdeFormat::Processed)?)
+}
+
+/// Reads a verifying key written by [`write_vk`] for an [`R1CSCircuit`] of
+/// width `WIDTH`.
+pub fn read_vk<P: Backend, const WIDTH: usize>(
+    params: &P,
+    reader: &mut impl io::Read,
+) -> Result<VerifyingKey<P::Curve>, Error> {
+    params.read_vk::<WIDTH>(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::square;
+    use crate::matrix::R1CS;
+
+    fn circuit<const WIDTH: usize>(r1cs: R1CS<Fr>, z: Vec<Fr>) -> R1CSCircuit<Fr, WIDTH> {
+        R1CSCircuit::new(r1cs, z).unwrap()
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let (r1cs, z) = square();
+        let circuit = circuit::<1>(r1cs, z);
+        let params = setup_for(&circuit);
+        let pk = keygen(&params, &circuit).unwrap();
+
+        let proof = prove(&params, &pk, circuit).unwrap();
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(9)], &proof).is_ok());
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(10)], &proof).is_err());
+
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(9)], &tampered).is_err());
+    }
+
+    #[test]
+    fn test_prove_wide() {
+        let (r1cs, z) = square();
+        let circuit = circuit::<4>(r1cs, z);
+        let params = setup_for(&circuit);
+        let pk = keygen(&params, &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit).unwrap();
+
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), &mut bytes).unwrap();
+        let vk = read_vk::<_, 4>(&params, &mut &bytes[..]).unwrap();
+        assert!(verify(&params, &vk, &[Fr::from(9)], &proof).is_ok());
+    }
+
+    #[test]
+    fn test_keygen_too_small() {
+        let (r1cs, z) = square();
+        let params = setup(3);
+
+        assert!(matches!(keygen(&params, &circuit::<1>(r1cs, z)), Err(Error::NotEnoughRows { k: 3, .. })));
+    }
+
+    #[test]
+    fn test_prove_unsatisfied() {
+        let (r1cs, mut z) = square();
+        z[1] = Fr::from(10);
+        let circuit = circuit::<1>(r1cs, z);
+        let params = setup(6);
+        let pk = keygen(&params, &circuit).unwrap();
+
+        assert!(matches!(prove(&params, &pk, circuit), Err(Error::Unsatisfied(_))));
+    }
+
+    #[test]
+    fn test_wrong_prime() {
+        let (r1cs, z) = square();
+
+        // The same constraints, as compiled by `circom --prime bls12381`.
+        let bls = crate::field::decimal_to_le_bytes(
+            "52435875175126190479447740508185965837690552500527637822603658699938581184513",
+        );
+        let r1cs = R1CS { prime: bls, ..r1cs };
+        assert!(matches!(R1CSCircuit::<Fr>::new(r1cs.clone(), z), Err(Error::FieldMismatch { .. })));
+        assert!(matches!(R1CSCircuit::<Fr>::without_witness(r1cs), Err(Error::FieldMismatch { .. })));
+    }
+
+    #[test]
+    fn test_ipa() {
+        use halo2_proofs::halo2curves::pasta::{EqAffine, Fp};
+
+        let mut r1cs = R1CS::new(1, 1);
+        r1cs.add_constraint(vec![(2, Fp::one())], vec![(2, Fp::one())], vec![(1, Fp::one())]);
+        let z = vec![Fp::one(), Fp::from(9), Fp::from(3)];
+        let circuit = R1CSCircuit::<Fp>::new(r1cs, z).unwrap();
+
+        let params: ParamsIPA<EqAffine> = setup_for_backend(&circuit);
+        let pk = keygen(&params, &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit).unwrap();
+        assert!(verify(&params, pk.get_vk(), &[Fp::from(9)], &proof).is_ok());
+        assert!(verify(&params, pk.get_vk(), &[Fp::from(10)], &proof).is_err());
+
+        let mut bytes = vec![];
+        write_params(&params, &mut bytes).unwrap();
+        let params = <ParamsIPA<EqAffine> as Backend>::read(&mut &bytes[..]).unwrap();
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), &mut bytes).unwrap();
+        let vk = read_vk::<_, 1>(&params, &mut &bytes[..]).unwrap();
+        assert!(verify(&params, &vk, &[Fp::from(9)], &proof).is_ok());
+    }
+
+    #[test]
+    fn test_serialize_keys() {
+        let (r1cs, z) = square();
+        let circuit = circuit::<1>(r1cs, z);
+        let params = setup(6);
+        let pk = keygen(&params, &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit.clone()).unwrap();
+
+        let mut bytes = vec![];
+        write_params(&params, &mut bytes).unwrap();
+        let params = read_params(&mut &bytes[..]).unwrap();
+
+        let mut bytes = vec![];
+        write_vk(pk.get_vk(), &mut bytes).unwrap();
+        let vk = read_vk::<_, 1>(&params, &mut &bytes[..]).unwrap();
+        assert!(verify(&params, &vk, &[Fr::from(9)], &proof).is_ok());
+
+        let pk = keygen_pk_from_vk(&params, vk, &circuit).unwrap();
+        let proof = prove(&params, &pk, circuit).unwrap();
+        assert!(verify(&params, pk.get_vk(), &[Fr::from(9)], &proof).is_ok());
+    }
+}
@@ -1,108 +1,524 @@
-use std::marker::PhantomData;
+use std::{array, fmt, marker::PhantomData, ops::Range};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{Cell, Value, Layouter, SimpleFloorPlanner},
-    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance},
     poly::Rotation,
 };
 
-// a*b-c = 0
+use crate::matrix::{Unsatisfied, R1CS};
+
+// (A·z) * (B·z) - (C·z) = 0
+//
+// Each linear combination is evaluated with a running sum into `acc`, taking
+// up to `WIDTH` terms per row from `z`/`coeff` and carrying the partial sum to
+// the next row. The three results are copied onto a single row of `a`, `b` and
+// `c` where the product gate is enabled. A wider layout spends more columns to
+// evaluate long linear combinations in fewer rows.
+//
+// The public inputs `z[1..=num_inputs]` are bound to `instance` by copy
+// constraints from the witness region, so the instance rows do not depend on
+// where the floor planner places any region.
 #[derive(Debug, Clone)]
-struct R1CSConfig {
-    a: Column<Advice>,
-    b: Column<Advice>,
-    sel: Column<Fixed>,
-    // c: Column<Instance>, // compiler warning: `c` is never read
+pub struct R1CSConfig<const WIDTH: usize = 1> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub sel: Column<Fixed>,
+    pub z: [Column<Advice>; WIDTH],
+    pub acc: Column<Advice>,
+    pub coeff: [Column<Fixed>; WIDTH],
+    pub sel_lc: Column<Fixed>,
+    pub constant: Column<Fixed>,
+    pub instance: Column<Instance>,
 }
 
 #[derive(Debug, Clone)]
-struct R1CSChip<F: FieldExt> {
-    config: R1CSConfig,
+pub struct R1CSChip<F: FieldExt, const WIDTH: usize = 1> {
+    config: R1CSConfig<WIDTH>,
+    names: Vec<String>,
+    num_inputs: usize,
     marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> R1CSChip<F> {
-    fn new(config: R1CSConfig) -> Self {
+impl<F: FieldExt, const WIDTH: usize> R1CSChip<F, WIDTH> {
+    pub fn new(config: R1CSConfig<WIDTH>) -> Self {
         R1CSChip {
             config,
+            names: vec![],
+            num_inputs: 0,
             marker: PhantomData,
         }
     }
+
+    /// Annotates witness cells with `names`, indexed by wire, instead of
+    /// their position in `z`.
+    pub fn with_names(mut self, names: Vec<String>) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Annotates the first `num_inputs` wires after the one wire as public
+    /// when they have no name.
+    pub fn with_num_inputs(mut self, num_inputs: usize) -> Self {
+        self.num_inputs = num_inputs;
+        self
+    }
+
+    /// The annotation of wire `i`, as [`R1CSCircuit::name`].
+    fn name(&self, i: usize) -> String {
+        match self.names.get(i) {
+            Some(name) => name.clone(),
+            None => crate::graph::default_name(self.num_inputs, i),
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> R1CSConfig<WIDTH> {
+        assert!(WIDTH > 0, "layout width must be positive");
+
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let z = array::from_fn(|_| meta.advice_column());
+        let acc = meta.advice_column();
+        let sel = meta.fixed_column();
+        let coeff = array::from_fn(|_| meta.fixed_column());
+        let sel_lc = meta.fixed_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+
+        for column in [a, b, c, acc].into_iter().chain(z) {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        meta.create_gate("sel*(c-a*b)", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            let sel = meta.query_fixed(sel, Rotation::cur());
+
+            vec![sel*(c - (a*b))]
+        });
+
+        meta.create_gate("sel_lc*(acc'-acc-sum(coeff*z))", |meta| {
+            let sum = z
+                .iter()
+                .zip(coeff.iter())
+                .map(|(z, coeff)| {
+                    meta.query_fixed(*coeff, Rotation::cur()) * meta.query_advice(*z, Rotation::cur())
+                })
+                .fold(Expression::Constant(F::zero()), |sum, term| sum + term);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let sel_lc = meta.query_fixed(sel_lc, Rotation::cur());
+
+            vec![sel_lc*(acc_next - acc - sum)]
+        });
+
+        R1CSConfig {
+            a,
+            b,
+            c,
+            sel,
+            z,
+            acc,
+            coeff,
+            sel_lc,
+            constant,
+            instance,
+        }
+    }
+
+    /// Evaluates `lc` starting at `offset`, returning the cell holding the sum
+    /// and the first free offset after it.
+    fn assign_lc(
+        &self,
+        region: &mut Region<'_, F>,
+        mut offset: usize,
+        lc: &[(usize, F)],
+        witness: &[AssignedCell<F, F>],
+    ) -> Result<(AssignedCell<F, F>, usize), Error> {
+        let mut acc = region.assign_advice_from_constant(|| "acc", self.config.acc, offset, F::zero())?;
+
+        for terms in lc.chunks(WIDTH) {
+            let mut sum = acc.value().copied();
+            for (i, (z_column, coeff_column)) in self.config.z.iter().zip(self.config.coeff.iter()).enumerate() {
+                match terms.get(i) {
+                    Some(&(index, coeff)) => {
+                        let z = witness.get(index).ok_or(Error::Synthesis)?;
+                        z.copy_advice(|| self.name(index), region, *z_column, offset)?;
+                        region.assign_fixed(|| "coeff", *coeff_column, offset, || Value::known(coeff))?;
+                        sum = sum + z.value().map(|z| *z * coeff);
+                    }
+                    None => {
+                        region.assign_advice(|| "pad", *z_column, offset, || Value::known(F::zero()))?;
+                        region.assign_fixed(|| "pad", *coeff_column, offset, || Value::known(F::zero()))?;
+                    }
+                }
+            }
+            region.assign_fixed(|| "sel_lc", self.config.sel_lc, offset, || Value::known(F::one()))?;
+
+            offset += 1;
+            acc = region.assign_advice(|| "acc", self.config.acc, offset, || sum)?;
+        }
+
+        Ok((acc, offset + 1))
+    }
+
+    /// Lays out one constraint starting at `offset`, returning the first free
+    /// offset after it.
+    fn assign_constraint_at(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: &[(usize, F)],
+        b: &[(usize, F)],
+        c: &[(usize, F)],
+        witness: &[AssignedCell<F, F>],
+    ) -> Result<usize, Error> {
+        let (a, offset) = self.assign_lc(region, offset, a, witness)?;
+        let (b, offset) = self.assign_lc(region, offset, b, witness)?;
+        let (c, offset) = self.assign_lc(region, offset, c, witness)?;
+
+        a.copy_advice(|| "a", region, self.config.a, offset)?;
+        b.copy_advice(|| "b", region, self.config.b, offset)?;
+        c.copy_advice(|| "c", region, self.config.c, offset)?;
+        region.assign_fixed(|| "sel", self.config.sel, offset, || Value::known(F::one()))?;
+        Ok(offset + 1)
+    }
+
+    /// Rows taken by a linear combination of `terms` terms.
+    pub fn lc_rows(terms: usize) -> usize {
+        terms.div_ceil(WIDTH) + 1
+    }
+
+    /// Rows taken by a constraint with the given numbers of terms.
+    pub fn constraint_rows(a: usize, b: usize, c: usize) -> usize {
+        Self::lc_rows(a) + Self::lc_rows(b) + Self::lc_rows(c) + 1
+    }
+
+    /// Rows taken by a witness vector of `n` entries.
+    pub fn witness_rows(n: usize) -> usize {
+        n.div_ceil(WIDTH)
+    }
+}
+
+/// Rows needed to lay out an [`R1CSCircuit`], used to pick the circuit size `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowBudget {
+    /// Rows of the witness region, `WIDTH` entries of `z` per row.
+    pub witness: usize,
+    /// Rows of all constraint regions.
+    pub constraints: usize,
+    /// Rows of the instance column, one per public input.
+    pub instance: usize,
+    /// Rows reserved for blinding factors.
+    pub blinding: usize,
+}
+
+impl RowBudget {
+    /// Usable rows required before blinding.
+    pub fn usable(&self) -> usize {
+        (self.witness + self.constraints).max(self.instance)
+    }
+
+    /// Total rows `2^k` must cover, including the blinding rows and the last
+    /// row halo2 reserves for the permutation argument.
+    pub fn total(&self) -> usize {
+        (self.usable() + self.blinding + 1).max(self.blinding + 3)
+    }
+
+    /// The smallest `k` with `2^k >= total()`.
+    pub fn min_k(&self) -> u32 {
+        self.total().next_power_of_two().trailing_zeros()
+    }
+}
+
+impl fmt::Display for RowBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} rows (witness {}, constraints {}, public inputs {}, blinding {} + 1)",
+            self.total(),
+            self.witness,
+            self.constraints,
+            self.instance,
+            self.blinding
+        )
+    }
 }
 
-trait R1CSComposer<F: FieldExt> {
-    fn assign_advice(
+pub trait R1CSComposer<F: FieldExt> {
+    /// Assigns the witness vector `z` once, with `z[0]` fixed to one.
+    fn assign_witness(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        z: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Constrains the witness cells `z[1..=num_inputs]` to the instance rows
+    /// `0..num_inputs`.
+    fn expose_public(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[AssignedCell<F, F>],
+        num_inputs: usize,
+    ) -> Result<(), Error>;
+
+    /// Enforces `(A·z) * (B·z) = C·z` against previously assigned witness cells.
+    fn assign_constraint(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &[(usize, F)],
+        b: &[(usize, F)],
+        c: &[(usize, F)],
+        witness: &[AssignedCell<F, F>],
+    ) -> Result<(), Error>;
+
+    /// Enforces the constraints `rows` of `r1cs` back to back in a single region.
+    fn assign_constraints(
         &self,
         layouter: &mut impl Layouter<F>,
-        a: F,
-        b: F,
+        r1cs: &R1CS<F>,
+        rows: Range<usize>,
+        witness: &[AssignedCell<F, F>],
     ) -> Result<(), Error>;
 }
 
-impl<F: FieldExt> R1CSComposer<F> for R1CSChip<F> {
+impl<F: FieldExt, const WIDTH: usize> R1CSComposer<F> for R1CSChip<F, WIDTH> {
+
+    fn assign_witness(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        z: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>
+    {
+        layouter.assign_region(
+            || "witness",
+            |mut region| {
+                z.iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let (column, offset) = (self.config.z[i % WIDTH], i / WIDTH);
+                        if i == 0 {
+                            region.assign_advice_from_constant(|| "one", column, offset, F::one())
+                        } else {
+                            region.assign_advice(|| self.name(i), column, offset, || *value)
+                        }
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[AssignedCell<F, F>],
+        num_inputs: usize,
+    ) -> Result<(), Error>
+    {
+        let inputs = witness.get(1..1 + num_inputs).ok_or(Error::Synthesis)?;
+        for (row, cell) in inputs.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), self.config.instance, row)?;
+        }
+        Ok(())
+    }
 
-    fn assign_advice(
+    fn assign_constraint(
         &self,
         layouter: &mut impl Layouter<F>,
-        a: F,
-        b: F,
+        a: &[(usize, F)],
+        b: &[(usize, F)],
+        c: &[(usize, F)],
+        witness: &[AssignedCell<F, F>],
     ) -> Result<(), Error>
     {
         layouter.assign_region(
-            || "a and b",
+            || "constraint",
             |mut region| {
-                    region.assign_advice(|| "a", self.config.a, 0, || Value::known(a))?;
-                    region.assign_advice(|| "b", self.config.b, 0, || Value::known(b))?;
-                    region.assign_fixed(|| "sel", self.config.sel, 0, || Value::known(F::one()))?;
+                self.assign_constraint_at(&mut region, 0, a, b, c, witness)?;
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_constraints(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        r1cs: &R1CS<F>,
+        rows: Range<usize>,
+        witness: &[AssignedCell<F, F>],
+    ) -> Result<(), Error>
+    {
+        layouter.assign_region(
+            || format!("constraints {}..{}", rows.start, rows.end),
+            |mut region| {
+                let mut offset = 0;
+                for i in rows.clone() {
+                    let (a, b, c) = r1cs.constraint(i);
+                    offset = self.assign_constraint_at(&mut region, offset, a, b, c, witness)?;
+                }
                 Ok(())
             },
         )
     }
 }
 
-#[derive(Default)]
-struct R1CSCircuit<F: FieldExt> {
-    a: Vec<F>,
-    b: Vec<F>,
+/// Proves knowledge of a witness `z` satisfying `r1cs`, with the public inputs
+/// `r1cs.public_inputs(z)` as the single instance column.
+///
+/// Constraints are laid out in regions of `chunk_size` constraints, or all in
+/// one region by default, which keeps floor planning cheap for large circuits.
+/// `WIDTH` is the number of linear combination terms evaluated per row; as
+/// const generic defaults are not used for inference, name the field to get
+/// the default, e.g. `R1CSCircuit::<Fr>::new(r1cs, z)?`.
+#[derive(Clone, Default)]
+pub struct R1CSCircuit<F: FieldExt, const WIDTH: usize = 1> {
+    r1cs: R1CS<F>,
+    z: Vec<Value<F>>,
+    chunk_size: Option<usize>,
+    names: Vec<String>,
 }
 
-impl<F: FieldExt> Circuit<F> for R1CSCircuit<F> {
-    type Config = R1CSConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+impl<F: FieldExt, const WIDTH: usize> R1CSCircuit<F, WIDTH> {
+    /// Fails with [`Error::FieldMismatch`](crate::Error::FieldMismatch) if
+    /// `r1cs` was compiled for another prime than the modulus of `F`, and with
+    /// [`Unsatisfied::WitnessLength`] if `z` does not have an entry for every
+    /// variable of `r1cs`.
+    pub fn new(r1cs: R1CS<F>, z: Vec<F>) -> Result<Self, crate::Error> {
+        r1cs.check_field()?;
+        if z.len() != r1cs.num_variables() {
+            return Err(Unsatisfied::WitnessLength {
+                expected: r1cs.num_variables(),
+                actual: z.len(),
+            }
+            .into());
+        }
+        Ok(R1CSCircuit {
+            r1cs,
+            z: z.into_iter().map(Value::known).collect(),
+            chunk_size: None,
+            names: vec![],
+        })
+    }
 
-    fn without_witnesses(&self) -> Self {
-        Self::default()
+    /// The circuit shape for `r1cs` with an unknown witness, as used for key
+    /// generation, failing like [`new`](Self::new).
+    pub fn without_witness(r1cs: R1CS<F>) -> Result<Self, crate::Error> {
+        r1cs.check_field()?;
+        Ok(Self::shape(r1cs))
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let a = meta.advice_column();
-        let b = meta.advice_column();
-        let c = meta.instance_column();
-        let sel = meta.fixed_column();
-        // meta.enable_equality(c);
+    fn shape(r1cs: R1CS<F>) -> Self {
+        let z = vec![Value::unknown(); r1cs.num_variables()];
+        R1CSCircuit {
+            r1cs,
+            z,
+            chunk_size: None,
+            names: vec![],
+        }
+    }
 
-        meta.create_gate("sel*(c-a*b)", |meta| {
-            let a = meta.query_advice(a, Rotation::cur());
-            let b = meta.query_advice(b, Rotation::cur());
-            let c = meta.query_instance(c, Rotation::cur());
-            let sel = meta.query_fixed(sel, Rotation::cur());
+    /// Lays out at most `chunk_size` constraints per region.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        self.chunk_size = Some(chunk_size);
+        self
+    }
 
-            vec![sel*(c - (a*b))]
-        });
+    /// Names the wires of `z`, e.g. from [`Symbols::names`](crate::circom::Symbols::names),
+    /// for cell annotations.
+    pub fn with_names(mut self, names: Vec<String>) -> Self {
+        self.names = names;
+        self
+    }
 
-        R1CSConfig {
-            a,
-            b,
-            sel,
-            // c,
+    /// The name of `wire`, or its position in `z` if unnamed.
+    pub fn name(&self, wire: usize) -> String {
+        match self.names.get(wire) {
+            Some(name) => name.clone(),
+            None => crate::graph::default_name(self.r1cs.num_inputs, wire),
+        }
+    }
+
+    pub fn r1cs(&self) -> &R1CS<F> {
+        &self.r1cs
+    }
+
+    /// The witness vector, if every entry of it is known.
+    pub fn known_witness(&self) -> Option<Vec<F>> {
+        let mut z = Vec::with_capacity(self.z.len());
+        for value in self.z.iter() {
+            value.map(|value| z.push(value));
+        }
+        (z.len() == self.z.len()).then_some(z)
+    }
+
+    /// Rows needed to lay out this circuit.
+    pub fn row_budget(&self) -> RowBudget {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+
+        RowBudget {
+            witness: R1CSChip::<F, WIDTH>::witness_rows(self.r1cs.num_variables()),
+            constraints: self
+                .r1cs
+                .constraints()
+                .map(|(a, b, c)| R1CSChip::<F, WIDTH>::constraint_rows(a.len(), b.len(), c.len()))
+                .sum(),
+            instance: self.r1cs.num_inputs,
+            blinding: meta.blinding_factors(),
+        }
+    }
+
+    /// The smallest circuit size `k` this circuit fits in.
+    pub fn min_k(&self) -> u32 {
+        self.row_budget().min_k()
+    }
+
+    /// Checks that this circuit fits in `2^k` rows.
+    pub fn check_k(&self, k: u32) -> Result<(), crate::Error> {
+        let budget = self.row_budget();
+        if k < budget.min_k() {
+            return Err(crate::Error::NotEnoughRows { k, budget });
         }
+        Ok(())
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize> Circuit<F> for R1CSCircuit<F, WIDTH> {
+    type Config = R1CSConfig<WIDTH>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            chunk_size: self.chunk_size,
+            names: self.names.clone(),
+            // The R1CS was checked when this circuit was built.
+            ..Self::shape(self.r1cs.clone())
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        R1CSChip::<F, WIDTH>::configure(meta)
     }
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
-        let cs = R1CSChip::new(config);
+        let cs = R1CSChip::new(config)
+            .with_names(self.names.clone())
+            .with_num_inputs(self.r1cs.num_inputs);
 
-        for i in 0..self.a.len() {
-            cs.assign_advice(&mut layouter, self.a[i], self.b[i])?;
+        let witness = cs.assign_witness(&mut layouter.namespace(|| "witness"), &self.z)?;
+        cs.expose_public(&mut layouter.namespace(|| "public inputs"), &witness, self.r1cs.num_inputs)?;
+        let n = self.r1cs.num_constraints();
+        let chunk_size = self.chunk_size.unwrap_or(n).max(1);
+        for start in (0..n).step_by(chunk_size) {
+            let rows = start..start.saturating_add(chunk_size).min(n);
+            let mut layouter = layouter.namespace(|| format!("constraints {}..{}", rows.start, rows.end));
+            cs.assign_constraints(&mut layouter, &self.r1cs, rows, &witness)?;
         }
 
         Ok(())
@@ -112,29 +528,117 @@ impl<F: FieldExt> Circuit<F> for R1CSCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::R1CSCircuit;
+    use crate::fixtures;
+    use crate::matrix::{Unsatisfied, R1CS};
     use halo2_proofs::circuit::Value;
     use halo2_proofs::halo2curves::bn256::Fr as Fp;
     use std::env;
+
+    fn cubic(x: u64) -> R1CSCircuit<Fp> {
+        R1CSCircuit::new(fixtures::cubic(), fixtures::cubic_witness(x)).unwrap()
+    }
+
     #[test]
     fn test_r1cs() {
         env::set_var("RUST_BACKTRACE", "full");
         use halo2_proofs::dev::MockProver;
 
-        let k = 4;
-        let a = vec![Fp::from(5), Fp::from(4), Fp::from(3)];
-        let b = vec![Fp::from(3), Fp::from(4), Fp::from(10)];
-        let c = vec![Fp::from(15), Fp::from(16), Fp::from(30)];
+        let circuit = cubic(3);
+        let k = circuit.min_k();
+        let public_inputs = vec![vec![Fp::from(35)]];
+
+        let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+        prover.assert_satisfied();
+    }
 
-        let circuit = R1CSCircuit {
-            a: a,
-            b: b,
-            // c: c.clone(),
-        };
+    #[test]
+    fn test_r1cs_bad_witness() {
+        use halo2_proofs::dev::MockProver;
 
-        let public_inputs = vec![c];
+        let k = 7;
+        let mut circuit = cubic(3);
+        circuit.z[1] = Value::known(Fp::from(36));
+        let public_inputs = vec![vec![Fp::from(36)]];
 
         let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
-        prover.assert_satisfied();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_r1cs_witness_length() {
+        let mut z = fixtures::cubic_witness(3);
+        z.pop();
+        assert!(matches!(
+            R1CSCircuit::<Fp>::new(fixtures::cubic(), z),
+            Err(crate::Error::Unsatisfied(Unsatisfied::WitnessLength { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_r1cs_bad_public_input() {
+        use halo2_proofs::dev::MockProver;
+
+        let k = 7;
+        let circuit = cubic(3);
+        let public_inputs = vec![vec![Fp::from(36)]];
+
+        let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_r1cs_chunks() {
+        use halo2_proofs::dev::MockProver;
+
+        for chunk_size in [1, 3, 4] {
+            let circuit = cubic(3).with_chunk_size(chunk_size);
+            let k = circuit.min_k();
+
+            let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(35)]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_r1cs_wide() {
+        use halo2_proofs::dev::MockProver;
+
+        // A long linear combination: sum(x_i) * 1 = out, with out public.
+        let n = 10;
+        let mut r1cs = R1CS::new(1, n);
+        let terms = (2..2 + n).map(|i| (i, Fp::from(i as u64))).collect();
+        r1cs.add_constraint(terms, vec![(0, Fp::one())], vec![(1, Fp::one())]);
+        let out: u64 = (2..2 + n as u64).map(|i| i * i).sum();
+        let z: Vec<Fp> = [1, out].into_iter().chain(2..2 + n as u64).map(Fp::from).collect();
+
+        let narrow = R1CSCircuit::<Fp, 1>::new(r1cs.clone(), z.clone()).unwrap();
+        let wide = R1CSCircuit::<Fp, 4>::new(r1cs, z).unwrap();
+        assert_eq!(narrow.row_budget().constraints, 11 + 2 + 2 + 1);
+        assert_eq!(wide.row_budget().constraints, 4 + 2 + 2 + 1);
+        assert_eq!(wide.row_budget().witness, 3);
+
+        for prover in [
+            MockProver::run(narrow.min_k(), &narrow, vec![vec![Fp::from(out)]]).unwrap(),
+            MockProver::run(wide.min_k(), &wide, vec![vec![Fp::from(out)]]).unwrap(),
+        ] {
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_row_budget() {
+        use halo2_proofs::dev::MockProver;
+
+        let circuit = cubic(3);
+        let budget = circuit.row_budget();
+        assert_eq!(budget.witness, 6);
+        assert_eq!(budget.constraints, 7 + 7 + 8 + 8);
+        assert_eq!(budget.instance, 1);
+
+        let k = circuit.min_k();
+        assert!(circuit.check_k(k).is_ok());
+        assert!(matches!(circuit.check_k(k - 1), Err(crate::Error::NotEnoughRows { .. })));
+        assert!(MockProver::run(k - 1, &circuit, vec![vec![Fp::from(35)]]).is_err());
     }
 
     #[cfg(feature = "dev-graph")]
@@ -146,20 +650,22 @@ mod tests {
         root.fill(&WHITE).unwrap();
         let root = root.titled("R1CS Layout", ("sans-serif", 60)).unwrap();
 
-        let circuit = R1CSCircuit::<Fp> {
-            a: vec![Fp::from(1), Fp::from(2)],//Value::unknown(), Value::unknown()],
-            b: vec![Fp::from(1), Fp::from(2)],//Value::unknown(), Value::unknown()],
-            // sel: vec![Fp::from(1), Fp::from(1)],
-            // c: vec![Fp::from(1), Fp::from(2)],//Value::unknown(), Value::unknown()],
-        };
+        let circuit = cubic(3);
         halo2_proofs::dev::CircuitLayout::default()
             .mark_equality_cells(true)
             .show_equality_constraints(true)
-            .render(4, &circuit, &root)
+            .render(7, &circuit, &root)
             .unwrap();
 
+        // Namespaces only: witness, public inputs and constraint chunks.
         let dot_string = halo2_proofs::dev::circuit_dot_graph(&circuit);
-        println!("---{}---", dot_string); // --> bug: is empty
+        assert!(dot_string.contains("witness"));
+        println!("---{}---", dot_string);
+
+        // Wires and constraints.
+        let dot_string = crate::graph::dot_graph(circuit.r1cs());
+        assert!(dot_string.contains("c3"));
+        println!("---{}---", dot_string);
         // let mut dot_graph = std::fs::File::create("circuit.dot").unwrap();
         // std::io::Write::write_all(&mut dot_graph, dot_string.as_bytes()).unwrap();
     }
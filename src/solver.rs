@@ -0,0 +1,212 @@
+//! Native witness generation for small [`R1CS`] instances.
+//!
+//! Starting from the public inputs and some private inputs, each constraint
+//! with a single unknown wire is solved for it, as long as the unknown does
+//! not appear in both A and B. This covers linear constraints as well as
+//! quotients such as `x * inv = 1`, and is enough for circuits that do not
+//! need hints from an external witness generator.
+
+use std::{collections::BTreeSet, fmt};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::matrix::R1CS;
+
+/// Reason [`solve`] could not complete a witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsolved {
+    /// The number of public inputs does not match the R1CS.
+    InputLength { expected: usize, actual: usize },
+    /// A given private input is not a private witness of the R1CS.
+    NotPrivate(usize),
+    /// Row `row` refers to column `col`, which is outside `z`.
+    UnknownVariable { row: usize, col: usize },
+    /// Constraint `row` cannot hold for the known values.
+    Conflict(usize),
+    /// No constraint determines the wires `wires`; `constraints` are those
+    /// left with unknowns.
+    Underdetermined { wires: Vec<usize>, constraints: Vec<usize> },
+}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputLength { expected, actual } => {
+                write!(f, "{} public inputs given, expected {}", actual, expected)
+            }
+            Self::NotPrivate(wire) => write!(f, "wire {} is not a private witness", wire),
+            Self::UnknownVariable { row, col } => {
+                write!(f, "constraint {} refers to unknown variable {}", row, col)
+            }
+            Self::Conflict(row) => write!(f, "constraint {} conflicts with the known values", row),
+            Self::Underdetermined { wires, constraints } => write!(
+                f,
+                "{} wires are not determined, by {} constraints with unknowns",
+                wires.len(),
+                constraints.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Unsolved {}
+
+/// Completes the witness vector `z` of `r1cs` from its public inputs and the
+/// private inputs `private`, given as `(wire, value)`.
+pub fn solve<F: FieldExt>(
+    r1cs: &R1CS<F>,
+    public_inputs: &[F],
+    private: &[(usize, F)],
+) -> Result<Vec<F>, Unsolved> {
+    if public_inputs.len() != r1cs.num_inputs {
+        return Err(Unsolved::InputLength {
+            expected: r1cs.num_inputs,
+            actual: public_inputs.len(),
+        });
+    }
+
+    let mut z = vec![None; r1cs.num_variables()];
+    z[0] = Some(F::one());
+    for (i, value) in public_inputs.iter().enumerate() {
+        z[1 + i] = Some(*value);
+    }
+    for &(wire, value) in private {
+        if wire <= r1cs.num_inputs || wire >= z.len() {
+            return Err(Unsolved::NotPrivate(wire));
+        }
+        z[wire] = Some(value);
+    }
+
+    let mut occurrences = vec![vec![]; z.len()];
+    for (row, (a, b, c)) in r1cs.constraints().enumerate() {
+        for &(col, _) in a.iter().chain(b).chain(c) {
+            let rows = occurrences.get_mut(col).ok_or(Unsolved::UnknownVariable { row, col })?;
+            if rows.last() != Some(&row) {
+                rows.push(row);
+            }
+        }
+    }
+
+    let mut worklist: BTreeSet<usize> = (0..r1cs.num_constraints()).collect();
+    while let Some(row) = worklist.pop_first() {
+        if let Some((wire, value)) = solve_constraint(r1cs, row, &z)? {
+            z[wire] = Some(value);
+            worklist.extend(occurrences[wire].iter().copied());
+        }
+    }
+
+    let wires: Vec<usize> = (0..z.len()).filter(|wire| z[*wire].is_none()).collect();
+    if !wires.is_empty() {
+        let constraints = (0..r1cs.num_constraints())
+            .filter(|row| {
+                let (a, b, c) = r1cs.constraint(*row);
+                a.iter().chain(b).chain(c).any(|(col, _)| z[*col].is_none())
+            })
+            .collect();
+        return Err(Unsolved::Underdetermined { wires, constraints });
+    }
+
+    Ok(z.into_iter().flatten().collect())
+}
+
+/// A linear combination split into its known part and the coefficient of
+/// its one unknown wire.
+struct Partial<F> {
+    known: F,
+    unknown: F,
+}
+
+/// Checks constraint `row` if it is fully known, or returns the value of its
+/// only unknown wire if it can be solved for.
+fn solve_constraint<F: FieldExt>(
+    r1cs: &R1CS<F>,
+    row: usize,
+    z: &[Option<F>],
+) -> Result<Option<(usize, F)>, Unsolved> {
+    let (a, b, c) = r1cs.constraint(row);
+    let unknowns: BTreeSet<usize> = a
+        .iter()
+        .chain(b)
+        .chain(c)
+        .map(|(col, _)| *col)
+        .filter(|col| z[*col].is_none())
+        .collect();
+    let wire = match unknowns.len() {
+        0 => None,
+        1 => unknowns.into_iter().next(),
+        _ => return Ok(None),
+    };
+
+    let split = |lc: &[(usize, F)]| {
+        lc.iter().fold(
+            Partial { known: F::zero(), unknown: F::zero() },
+            |mut sum, (col, coeff)| {
+                match z[*col] {
+                    Some(value) => sum.known += *coeff * value,
+                    None => sum.unknown += *coeff,
+                }
+                sum
+            },
+        )
+    };
+    let (a, b, c) = (split(a), split(b), split(c));
+
+    let wire = match wire {
+        Some(wire) => wire,
+        None if a.known * b.known == c.known => return Ok(None),
+        None => return Err(Unsolved::Conflict(row)),
+    };
+    if a.unknown != F::zero() && b.unknown != F::zero() {
+        return Ok(None);
+    }
+
+    // (a + ka x)(b + kb x) = c + kc x with ka kb = 0 is linear in x.
+    let numerator = c.known - a.known * b.known;
+    let denominator = a.unknown * b.known + b.unknown * a.known - c.unknown;
+    match Option::<F>::from(denominator.invert()) {
+        Some(inverse) => Ok(Some((wire, numerator * inverse))),
+        None if numerator == F::zero() => Ok(None),
+        None => Err(Unsolved::Conflict(row)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, Unsolved};
+    use crate::fixtures::{cubic, cubic_witness};
+    use crate::R1CS;
+    use halo2_proofs::halo2curves::bn256::Fr as Fp;
+
+    #[test]
+    fn test_solve() {
+        let r1cs = cubic();
+        let z = solve(&r1cs, &[Fp::from(35)], &[(2, Fp::from(3))]).unwrap();
+        assert_eq!(z, cubic_witness(3));
+        assert_eq!(r1cs.is_satisfied(&z), Ok(()));
+    }
+
+    #[test]
+    fn test_solve_quotient() {
+        // x * inv = 1, with x public
+        let mut r1cs = R1CS::new(1, 1);
+        r1cs.add_constraint(vec![(1, Fp::one())], vec![(2, Fp::one())], vec![(0, Fp::one())]);
+
+        let z = solve(&r1cs, &[Fp::from(7)], &[]).unwrap();
+        assert_eq!(z[2], Fp::from(7).invert().unwrap());
+        assert_eq!(solve(&r1cs, &[Fp::zero()], &[]), Err(Unsolved::Conflict(0)));
+    }
+
+    #[test]
+    fn test_solve_conflict_and_underdetermined() {
+        let r1cs = cubic();
+        assert_eq!(
+            solve(&r1cs, &[Fp::from(36)], &[(2, Fp::from(3))]),
+            Err(Unsolved::Conflict(3))
+        );
+
+        // x is only fixed by the cubic, which has no single unknown.
+        match solve(&r1cs, &[Fp::from(35)], &[]) {
+            Err(Unsolved::Underdetermined { wires, .. }) => assert!(wires.contains(&2)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}